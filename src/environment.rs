@@ -1,4 +1,8 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 use crate::{
     syntax::{token::Token, value::Value},
@@ -12,6 +16,8 @@ type Result<T> = std::result::Result<T, Exception>;
 pub struct Environment {
     pub enclosing: Option<EnvRef>,
     values: HashMap<String, Value>,
+    // Names declared with `const`, locals are already checked by the resolver
+    constants: HashSet<String>,
 }
 
 impl Environment {
@@ -19,6 +25,7 @@ impl Environment {
         Rc::new(RefCell::new(Environment {
             enclosing: None,
             values: HashMap::new(),
+            constants: HashSet::new(),
         }))
     }
 
@@ -26,6 +33,7 @@ impl Environment {
         Rc::new(RefCell::new(Environment {
             enclosing: Some(enclosing.clone()),
             values: HashMap::new(),
+            constants: HashSet::new(),
         }))
     }
 
@@ -33,6 +41,25 @@ impl Environment {
         self.values.insert(name, value);
     }
 
+    // Declarations in Lox code, which may not replace a constant
+    pub fn declare(&mut self, name: &Token, value: Value) -> Result<()> {
+        if self.constants.contains(&name.lexeme) {
+            return Exception::runtime_error(
+                name.clone(),
+                format!("Cannot redeclare constant '{}'.", name.lexeme),
+            );
+        }
+
+        self.define(name.lexeme.clone(), value);
+        Ok(())
+    }
+
+    pub fn define_constant(&mut self, name: &Token, value: Value) -> Result<()> {
+        self.declare(name, value)?;
+        self.constants.insert(name.lexeme.clone());
+        Ok(())
+    }
+
     pub fn get_at(&self, distance: usize, name: &String) -> Result<Value> {
         if distance == 0 {
             return Ok(self.values.get(name).unwrap().clone());
@@ -45,15 +72,21 @@ impl Environment {
         panic!("Could not find local scope that variable belongs to!")
     }
 
-    pub fn assign_at(&mut self, distance: usize, name: &Token, value: &Value) {
+    pub fn assign_at(&mut self, distance: usize, name: &Token, value: &Value) -> Result<()> {
         if distance == 0 {
+            if self.constants.contains(&name.lexeme) {
+                return Exception::runtime_error(
+                    name.clone(),
+                    format!("Cannot assign to constant '{}'.", name.lexeme),
+                );
+            }
+
             self.values.insert(name.lexeme.clone(), value.clone());
-            return;
+            return Ok(());
         }
 
         if let Some(enclosing) = &self.enclosing {
-            enclosing.borrow_mut().assign_at(distance - 1, name, value);
-            return;
+            return enclosing.borrow_mut().assign_at(distance - 1, name, value);
         }
 
         panic!("Could not find local scope that variable belongs to!")
@@ -76,6 +109,13 @@ impl Environment {
     }
 
     pub fn assign(&mut self, name: &Token, value: Value) -> Result<Value> {
+        if self.constants.contains(&name.lexeme) {
+            return Exception::runtime_error(
+                name.clone(),
+                format!("Cannot assign to constant '{}'.", name.lexeme),
+            );
+        }
+
         if self.values.contains_key(&name.lexeme) {
             self.values.insert(name.lexeme.clone(), value.clone());
             return Ok(value);
@@ -10,6 +10,7 @@ pub trait Callable {
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Value>,
+        paren: &Token,
     ) -> Result<Value, Exception>;
     fn check_arity(&self, args_len: usize, current_token: &Token) -> Result<(), Exception> {
         if args_len != self.arity() {
@@ -2,44 +2,77 @@ use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
 
 use crate::{
     interpreter::Interpreter,
-    syntax::{token::Token, value::Value},
+    syntax::{stmt::Stmt, token::Token, token_type::TokenType, value::Value},
     Exception, RuntimeError,
 };
 
-use super::{callable::Callable, function::Function};
+use super::{callable::Callable, function::Function, traits::TraitRef};
 
 type Result<T> = std::result::Result<T, Exception>;
 
-#[derive(Debug, Clone)]
+// Classes are reference values: every instance (and every subclass) points to
+// the very same class object, so identity can be compared with `Rc::ptr_eq`.
+pub type ClassRef = Rc<Class>;
+
+#[derive(Debug)]
 pub struct Class {
+    // Declared without a body, including the ones required by traits
+    abstract_methods: Vec<String>,
+    // Class-level (static) fields, they can be mutated through any reference
+    fields: RefCell<HashMap<String, Value>>,
     getters: HashMap<String, Function>,
     name: String,
     methods: HashMap<String, Function>,
+    // Keyed by `#name`, kept apart so they never show up as public methods
+    private_methods: HashMap<String, Function>,
+    setters: HashMap<String, Function>,
     static_methods: HashMap<String, Function>,
-    super_class: Option<Box<Class>>,
+    super_class: Option<ClassRef>,
+    traits: Vec<TraitRef>,
+}
+
+// The members a class declaration evaluates to, keyed by name
+#[derive(Debug, Default)]
+pub struct ClassMembers {
+    pub abstract_methods: Vec<String>,
+    pub getters: HashMap<String, Function>,
+    pub methods: HashMap<String, Function>,
+    pub private_methods: HashMap<String, Function>,
+    pub setters: HashMap<String, Function>,
+    pub static_methods: HashMap<String, Function>,
 }
 
 impl Class {
     pub fn new(
-        getters: HashMap<String, Function>,
         name: String,
-        methods: HashMap<String, Function>,
-        static_methods: HashMap<String, Function>,
-        super_class: Option<Box<Class>>,
-    ) -> Class {
-        Class {
-            getters,
+        members: ClassMembers,
+        super_class: Option<ClassRef>,
+        traits: Vec<TraitRef>,
+    ) -> ClassRef {
+        Rc::new(Class {
+            abstract_methods: members.abstract_methods,
+            fields: RefCell::new(HashMap::new()),
+            getters: members.getters,
             name,
-            methods,
-            static_methods,
+            methods: members.methods,
+            private_methods: members.private_methods,
+            setters: members.setters,
+            static_methods: members.static_methods,
             super_class,
-        }
+            traits,
+        })
     }
 
-    pub fn get(&self, name: &Token) -> Result<Value> {
-        match self.static_methods.get(&name.lexeme) {
-            Some(method) => Ok(Value::Function(method.clone())),
-            None => Exception::runtime_error(
+    // Both static fields and static methods are inherited.
+    // Static methods are bound to `class_ref`, so `this` is the class they were called on.
+    pub fn get(&self, name: &Token, class_ref: ClassRef) -> Result<Value> {
+        if let Some(value) = self.find_field(&name.lexeme) {
+            return Ok(value);
+        }
+
+        match self.find_static_method(&name.lexeme) {
+            Some(Value::Function(method)) => Ok(Value::Function(method.bind_class(class_ref))),
+            _ => Exception::runtime_error(
                 name.clone(),
                 format!(
                     "Class doesn't have a static method called \"{}\".",
@@ -49,10 +82,126 @@ impl Class {
         }
     }
 
+    // Static fields are always written in the class they were assigned through,
+    // so a subclass shadows its super class' field instead of overwriting it.
+    pub fn set(&self, name: &Token, value: &Value) -> Result<()> {
+        if name.token_type == TokenType::Class {
+            return Exception::runtime_error(name.clone(), "Can't assign to 'class'.".into());
+        }
+
+        self.fields
+            .borrow_mut()
+            .insert(name.lexeme.clone(), value.clone());
+        Ok(())
+    }
+
+    fn find_field(&self, name: &String) -> Option<Value> {
+        self.fields.borrow().get(name).cloned().or(self
+            .super_class
+            .as_ref()
+            .and_then(|super_class| super_class.find_field(name)))
+    }
+
+    pub fn find_static_method(&self, name: &String) -> Option<Value> {
+        self.static_methods
+            .get(name)
+            .map(|f| Value::Function(f.clone()))
+            .or(self
+                .super_class
+                .as_ref()
+                .and_then(|super_class| super_class.find_static_method(name)))
+    }
+
     pub fn find_getter(&self, name: &Token) -> Option<Value> {
         self.getters
             .get(&name.lexeme)
             .map(|f| Value::Function(f.clone()))
+            .or(self
+                .super_class
+                .as_ref()
+                .and_then(|super_class| super_class.find_getter(name)))
+    }
+
+    pub fn find_setter(&self, name: &Token) -> Option<Value> {
+        self.setters
+            .get(&name.lexeme)
+            .map(|f| Value::Function(f.clone()))
+            .or(self
+                .super_class
+                .as_ref()
+                .and_then(|super_class| super_class.find_setter(name)))
+    }
+
+    // Private methods are never inherited, so only this class is searched
+    pub fn find_private_method(&self, name: &String) -> Option<Value> {
+        self.private_methods
+            .get(name)
+            .map(|f| Value::Function(f.clone()))
+    }
+
+    // Abstract methods (from this class or any super class) without a concrete implementation
+    pub fn unimplemented_methods(&self) -> Vec<String> {
+        let mut unimplemented: Vec<String> = vec![];
+        let mut class = Some(self);
+
+        while let Some(current) = class {
+            for name in &current.abstract_methods {
+                if self.find_method(name).is_none() && !unimplemented.contains(name) {
+                    unimplemented.push(name.clone());
+                }
+            }
+            class = current.super_class.as_deref();
+        }
+
+        unimplemented
+    }
+
+    pub fn inherits(&self, other: &ClassRef) -> bool {
+        std::ptr::eq(self, other.as_ref())
+            || self
+                .super_class
+                .as_ref()
+                .is_some_and(|super_class| super_class.inherits(other))
+    }
+
+    // Traits mixed into a super class are implemented by its subclasses too
+    pub fn implements(&self, other: &TraitRef) -> bool {
+        self.traits.iter().any(|t| Rc::ptr_eq(t, other))
+            || self
+                .super_class
+                .as_ref()
+                .is_some_and(|super_class| super_class.implements(other))
+    }
+
+    // Public method names, including the inherited ones, sorted
+    pub fn method_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        let mut class = Some(self);
+
+        while let Some(current) = class {
+            for name in current.methods.keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            class = current.super_class.as_deref();
+        }
+
+        names.sort();
+        names
+    }
+
+    // Parameters of `init`, patterns match the fields named after them
+    pub fn init_parameters(&self) -> Vec<String> {
+        match self.find_method(&"init".into()) {
+            Some(Value::Function(initializer)) => match initializer.declaration.as_ref() {
+                Stmt::Function { parameters, .. } => {
+                    parameters.iter().map(|p| p.lexeme.clone()).collect()
+                }
+                _ => vec![],
+            },
+            _ => vec![],
+        }
     }
 
     pub fn find_method(&self, name: &String) -> Option<Value> {
@@ -66,7 +215,7 @@ impl Class {
     }
 }
 
-impl Callable for Class {
+impl Callable for ClassRef {
     fn arity(&self) -> usize {
         if let Some(initializer) = self.find_method(&"init".into()) {
             match initializer {
@@ -78,12 +227,32 @@ impl Callable for Class {
         0
     }
 
-    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value> {
-        let instance = ClassInstance::new(self.clone());
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        args: Vec<Value>,
+        paren: &Token,
+    ) -> Result<Value> {
+        let unimplemented = self.unimplemented_methods();
+        if !unimplemented.is_empty() {
+            return Exception::runtime_error(
+                paren.clone(),
+                format!(
+                    "Can't instantiate abstract class '{}', missing implementation of '{}'.",
+                    self.name,
+                    unimplemented.join("', '")
+                ),
+            );
+        }
+
+        interpreter.allocate(std::mem::size_of::<ClassInstance>())?;
+        let instance = ClassInstance::new(Rc::clone(self));
 
         if let Some(method) = self.find_method(&"init".into()) {
             if let Value::Function(initializer) = method {
-                initializer.bind(instance.clone()).call(interpreter, args)?;
+                initializer
+                    .bind(instance.clone())
+                    .call(interpreter, args, paren)?;
             }
         }
 
@@ -108,17 +277,23 @@ impl Display for Class {
 // Without the changes I've made.
 pub type ClassInstanceRef = Rc<RefCell<ClassInstance>>;
 
+// Private fields are keyed by the class that declared them as well,
+// so a subclass can't read (or clash with) its super class' private state.
+type PrivateKey = (*const Class, String);
+
 #[derive(Debug, Clone)]
 pub struct ClassInstance {
-    pub class: Class,
+    pub class: ClassRef,
     fields: HashMap<String, Value>,
+    private_fields: HashMap<PrivateKey, Value>,
 }
 
 impl ClassInstance {
-    pub fn new(class: Class) -> ClassInstanceRef {
+    pub fn new(class: ClassRef) -> ClassInstanceRef {
         let instance = Self {
             class,
             fields: HashMap::new(),
+            private_fields: HashMap::new(),
         };
 
         Rc::new(RefCell::new(instance))
@@ -130,6 +305,11 @@ impl ClassInstance {
         instance_ref: ClassInstanceRef,
         interpreter: &mut Interpreter,
     ) -> Result<Value> {
+        // `instance.class` is reserved: it always evaluates to the class object itself
+        if name.token_type == TokenType::Class {
+            return Ok(Value::Class(Rc::clone(&self.class)));
+        }
+
         match self.fields.get(&name.lexeme) {
             // Field
             Some(value) => Ok(value.clone()),
@@ -138,7 +318,7 @@ impl ClassInstance {
                 // Looking for a field implicitly implies that fields shadow getters
                 if let Some(Value::Function(getter)) = self.class.find_getter(name) {
                     let bound_getter = getter.bind(instance_ref);
-                    let value = bound_getter.call(interpreter, vec![])?;
+                    let value = bound_getter.call(interpreter, vec![], name)?;
                     return Ok(value);
                 }
 
@@ -157,7 +337,53 @@ impl ClassInstance {
         }
     }
 
+    // `class` is the class whose method is accessing the member
+    pub fn get_private(
+        &self,
+        name: &Token,
+        class: &ClassRef,
+        instance_ref: ClassInstanceRef,
+    ) -> Result<Value> {
+        let key = (Rc::as_ptr(class), name.lexeme.clone());
+        if let Some(value) = self.private_fields.get(&key) {
+            return Ok(value.clone());
+        }
+
+        if let Some(Value::Function(method)) = class.find_private_method(&name.lexeme) {
+            return Ok(Value::Function(method.bind(instance_ref)));
+        }
+
+        Exception::runtime_error(
+            name.clone(),
+            format!("Undefined private member '{}'.", name.lexeme),
+        )
+    }
+
+    // Public fields, sorted so reflection output is deterministic
+    pub fn field_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.fields.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<Value> {
+        self.fields.get(name).cloned()
+    }
+
+    pub fn set_field(&mut self, name: String, value: Value) {
+        self.fields.insert(name, value);
+    }
+
+    pub fn set_private(&mut self, name: &Token, class: &ClassRef, value: &Value) {
+        let key = (Rc::as_ptr(class), name.lexeme.clone());
+        self.private_fields.insert(key, value.clone());
+    }
+
     pub fn set(&mut self, name: &Token, value: &Value) -> Result<()> {
+        if name.token_type == TokenType::Class {
+            return Exception::runtime_error(name.clone(), "Can't assign to 'class'.".into());
+        }
+
         let key = name.lexeme.clone();
         self.fields.insert(key, value.clone());
         Ok(())
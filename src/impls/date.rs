@@ -0,0 +1,434 @@
+use std::fmt::Display;
+
+use crate::{
+    natives::{expect_number, expect_string},
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+use super::function::NativeFunction;
+
+type Result<T> = std::result::Result<T, Exception>;
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+// Starting on Monday, like ISO-8601 weekdays
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+// A point in time, always in UTC. Durations are plain numbers of milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Date {
+    // Since the Unix epoch
+    millis: i64,
+}
+
+// Calendar fields of a date
+struct Fields {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    millisecond: i64,
+}
+
+impl Date {
+    // Only four digit years, so every date can be written as ISO-8601
+    pub fn from_timestamp(millis: f64) -> std::result::Result<Date, String> {
+        let min = days_from_civil(0, 1, 1) * MILLIS_PER_DAY;
+        let max = days_from_civil(10000, 1, 1) * MILLIS_PER_DAY;
+        if !millis.is_finite() || millis < min as f64 || millis >= max as f64 {
+            return Err("dates must be between the years 0 and 9999".into());
+        }
+
+        Ok(Date {
+            millis: millis.floor() as i64,
+        })
+    }
+
+    // Year, month and day, then optionally hour, minute, second and millisecond
+    pub fn from_components(components: &[f64]) -> std::result::Result<Date, String> {
+        if !(3..=7).contains(&components.len()) {
+            return Err(format!(
+                "expected between 3 and 7 components, but got {}",
+                components.len()
+            ));
+        }
+
+        let mut fields = [0, 1, 1, 0, 0, 0, 0];
+        for (i, component) in components.iter().enumerate() {
+            if component.fract() != 0.0 {
+                return Err(format!(
+                    "components must be integers, but got {}",
+                    component
+                ));
+            }
+            fields[i] = *component as i64;
+        }
+
+        Date::from_fields(Fields {
+            year: fields[0],
+            month: fields[1],
+            day: fields[2],
+            hour: fields[3],
+            minute: fields[4],
+            second: fields[5],
+            millisecond: fields[6],
+        })
+    }
+
+    // `2024-03-01`, `2024-03-01T12:30`, `2024-03-01T12:30:15.250Z` or with an offset like `+02:00`
+    pub fn parse(text: &str) -> std::result::Result<Date, String> {
+        let invalid = || format!("'{}' isn't an ISO-8601 date", text);
+        let mut reader = Reader {
+            bytes: text.as_bytes(),
+            current: 0,
+        };
+
+        let year = reader.number(4).ok_or_else(invalid)?;
+        reader.expect(b'-').ok_or_else(invalid)?;
+        let month = reader.number(2).ok_or_else(invalid)?;
+        reader.expect(b'-').ok_or_else(invalid)?;
+        let day = reader.number(2).ok_or_else(invalid)?;
+
+        let (mut hour, mut minute, mut second, mut millisecond, mut offset) = (0, 0, 0, 0, 0);
+        if reader
+            .expect(b'T')
+            .or_else(|| reader.expect(b' '))
+            .is_some()
+        {
+            hour = reader.number(2).ok_or_else(invalid)?;
+            reader.expect(b':').ok_or_else(invalid)?;
+            minute = reader.number(2).ok_or_else(invalid)?;
+
+            if reader.expect(b':').is_some() {
+                second = reader.number(2).ok_or_else(invalid)?;
+
+                // Anything after the milliseconds is dropped
+                if reader.expect(b'.').is_some() {
+                    let digits = reader.digits();
+                    if digits.is_empty() {
+                        return Err(invalid());
+                    }
+                    let padded = format!("{:0<3}", &digits[..digits.len().min(3)]);
+                    millisecond = padded.parse().map_err(|_| invalid())?;
+                }
+            }
+
+            if reader.expect(b'Z').is_none() {
+                if let Some(sign) = reader.expect(b'+').or_else(|| reader.expect(b'-')) {
+                    let hours = reader.number(2).ok_or_else(invalid)?;
+                    reader.expect(b':');
+                    let minutes = reader.number(2).ok_or_else(invalid)?;
+                    offset = (hours * 60 + minutes) * 60_000;
+                    if sign == b'-' {
+                        offset = -offset;
+                    }
+                }
+            }
+        }
+
+        if !reader.is_at_end() {
+            return Err(invalid());
+        }
+
+        let local = Date::from_fields(Fields {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            millisecond,
+        })?;
+        Date::from_timestamp((local.millis - offset) as f64)
+    }
+
+    fn from_fields(fields: Fields) -> std::result::Result<Date, String> {
+        let ranges = [
+            ("year", fields.year, 0, 9999),
+            ("month", fields.month, 1, 12),
+            (
+                "day",
+                fields.day,
+                1,
+                days_in_month(fields.year, fields.month),
+            ),
+            ("hour", fields.hour, 0, 23),
+            ("minute", fields.minute, 0, 59),
+            ("second", fields.second, 0, 59),
+            ("millisecond", fields.millisecond, 0, 999),
+        ];
+        for (name, value, min, max) in ranges {
+            if value < min || value > max {
+                return Err(format!(
+                    "{} must be between {} and {}, but got {}",
+                    name, min, max, value
+                ));
+            }
+        }
+
+        let days = days_from_civil(fields.year, fields.month, fields.day);
+        let time =
+            ((fields.hour * 60 + fields.minute) * 60 + fields.second) * 1000 + fields.millisecond;
+        Ok(Date {
+            millis: days * MILLIS_PER_DAY + time,
+        })
+    }
+
+    fn fields(&self) -> Fields {
+        let days = self.millis.div_euclid(MILLIS_PER_DAY);
+        let time = self.millis.rem_euclid(MILLIS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        Fields {
+            year,
+            month,
+            day,
+            hour: time / 3_600_000,
+            minute: time / 60_000 % 60,
+            second: time / 1000 % 60,
+            millisecond: time % 1000,
+        }
+    }
+
+    // 1 is Monday and 7 is Sunday
+    fn weekday(&self) -> i64 {
+        // The epoch was on a Thursday
+        (self.millis.div_euclid(MILLIS_PER_DAY) + 3).rem_euclid(7) + 1
+    }
+
+    fn day_of_year(&self) -> i64 {
+        let fields = self.fields();
+        days_from_civil(fields.year, fields.month, fields.day) - days_from_civil(fields.year, 1, 1)
+            + 1
+    }
+
+    // strftime-like directives: %Y %y %m %d %H %M %S %L %j %B %b %A %a %%
+    pub fn format(&self, pattern: &str) -> std::result::Result<String, String> {
+        let fields = self.fields();
+        let mut formatted = String::new();
+        let mut chars = pattern.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                formatted.push(c);
+                continue;
+            }
+
+            let directive = match chars.next() {
+                Some('Y') => format!("{:04}", fields.year),
+                Some('y') => format!("{:02}", fields.year % 100),
+                Some('m') => format!("{:02}", fields.month),
+                Some('d') => format!("{:02}", fields.day),
+                Some('H') => format!("{:02}", fields.hour),
+                Some('M') => format!("{:02}", fields.minute),
+                Some('S') => format!("{:02}", fields.second),
+                Some('L') => format!("{:03}", fields.millisecond),
+                Some('j') => format!("{:03}", self.day_of_year()),
+                Some('B') => MONTHS[fields.month as usize - 1].to_string(),
+                Some('b') => MONTHS[fields.month as usize - 1][..3].to_string(),
+                Some('A') => WEEKDAYS[self.weekday() as usize - 1].to_string(),
+                Some('a') => WEEKDAYS[self.weekday() as usize - 1][..3].to_string(),
+                Some('%') => "%".to_string(),
+                Some(other) => return Err(format!("unknown directive '%{}'", other)),
+                None => return Err("the pattern can't end with '%'".into()),
+            };
+            formatted.push_str(&directive);
+        }
+
+        Ok(formatted)
+    }
+
+    // Calendar fields, plus methods bound to the date like generators' `next()`
+    pub fn get(&self, name: &Token) -> Result<Value> {
+        let fields = self.fields();
+        let field = match name.lexeme.as_str() {
+            "year" => fields.year,
+            "month" => fields.month,
+            "day" => fields.day,
+            "hour" => fields.hour,
+            "minute" => fields.minute,
+            "second" => fields.second,
+            "millisecond" => fields.millisecond,
+            "weekday" => self.weekday(),
+            "dayOfYear" => self.day_of_year(),
+            "timestamp" => self.millis,
+            _ => {
+                let method = match name.lexeme.as_str() {
+                    "format" => NativeFunction::new(1, |_, paren, args| {
+                        let pattern = expect_string(paren, &args[1], "format")?;
+                        match Date::expect(&args[0]).format(&pattern) {
+                            Ok(formatted) => Ok(Value::String(formatted)),
+                            Err(message) => Exception::runtime_error(
+                                paren.clone(),
+                                format!("Invalid format pattern: {}.", message),
+                            ),
+                        }
+                    }),
+                    // Adds a duration in milliseconds, negative ones go back in time
+                    "add" => NativeFunction::new(1, |_, paren, args| {
+                        let duration = expect_number(paren, &args[1], "add")?;
+                        let millis = Date::expect(&args[0]).millis as f64 + duration;
+                        match Date::from_timestamp(millis) {
+                            Ok(date) => Ok(Value::Date(date)),
+                            Err(message) => Exception::runtime_error(
+                                paren.clone(),
+                                format!("Invalid date: {}.", message),
+                            ),
+                        }
+                    }),
+                    // Milliseconds from `other` to this date
+                    "since" => NativeFunction::new(1, |_, paren, args| match &args[1] {
+                        Value::Date(other) => Ok(Value::Number(
+                            (Date::expect(&args[0]).millis - other.millis) as f64,
+                        )),
+                        value => Exception::runtime_error(
+                            paren.clone(),
+                            format!("since() expected a date, but got {}.", value.type_name()),
+                        ),
+                    }),
+                    _ => {
+                        return Exception::runtime_error(
+                            name.clone(),
+                            format!("Undefined property '{}'.", name.lexeme),
+                        )
+                    }
+                };
+                return Ok(Value::NativeFunction(method.bind(Value::Date(*self))));
+            }
+        };
+
+        Ok(Value::Number(field as f64))
+    }
+
+    fn expect(value: &Value) -> Date {
+        match value {
+            Value::Date(date) => *date,
+            _ => panic!("Method is not bound to a date!"),
+        }
+    }
+}
+
+// ISO-8601, e.g. `2024-03-01T12:30:15.250Z`
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields = self.fields();
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            fields.year,
+            fields.month,
+            fields.day,
+            fields.hour,
+            fields.minute,
+            fields.second,
+            fields.millisecond
+        )
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    current: usize,
+}
+
+impl Reader<'_> {
+    // Exactly `width` digits
+    fn number(&mut self, width: usize) -> Option<i64> {
+        let digits = self.bytes.get(self.current..self.current + width)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        self.current += width;
+        std::str::from_utf8(digits).ok()?.parse().ok()
+    }
+
+    fn digits(&mut self) -> String {
+        let start = self.current;
+        while self.bytes.get(self.current).is_some_and(u8::is_ascii_digit) {
+            self.current += 1;
+        }
+        String::from_utf8_lossy(&self.bytes[start..self.current]).into_owned()
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<u8> {
+        if self.bytes.get(self.current) != Some(&expected) {
+            return None;
+        }
+
+        self.current += 1;
+        Some(expected)
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current == self.bytes.len()
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since the Unix epoch, see http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400;
+
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
@@ -0,0 +1,115 @@
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    impls::function::NativeFunction,
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+// Enums and their variants are singletons, so they're compared by identity
+pub type EnumRef = Rc<Enum>;
+pub type EnumVariantRef = Rc<EnumVariant>;
+
+#[derive(Debug)]
+pub struct Enum {
+    pub name: String,
+    variants: Vec<EnumVariantRef>,
+}
+
+impl Enum {
+    pub fn new(name: String, variants: &[Token]) -> EnumRef {
+        Rc::new_cyclic(|enum_ref| Enum {
+            variants: variants
+                .iter()
+                .enumerate()
+                .map(|(ordinal, variant)| {
+                    Rc::new(EnumVariant {
+                        enum_ref: enum_ref.clone(),
+                        name: variant.lexeme.clone(),
+                        ordinal,
+                    })
+                })
+                .collect(),
+            name,
+        })
+    }
+
+    // Besides its variants, an enum only has the `values()` method
+    pub fn get(&self, name: &Token, enum_ref: EnumRef) -> Result<Value> {
+        if let Some(variant) = self.variants.iter().find(|v| v.name == name.lexeme) {
+            return Ok(Value::EnumVariant(variant.clone()));
+        }
+
+        if name.lexeme == "values" {
+            let values = NativeFunction::new(0, |_, _, args| match &args[0] {
+                Value::Enum(enum_ref) => Ok(enum_ref.values()),
+                _ => panic!("values() is not bound to an enum!"),
+            });
+            return Ok(Value::NativeFunction(values.bind(Value::Enum(enum_ref))));
+        }
+
+        Exception::runtime_error(
+            name.clone(),
+            format!(
+                "Enum '{}' doesn't have a variant called '{}'.",
+                self.name, name.lexeme
+            ),
+        )
+    }
+
+    pub fn values(&self) -> Value {
+        let values = self
+            .variants
+            .iter()
+            .map(|variant| Value::EnumVariant(variant.clone()))
+            .collect();
+        Value::List(Rc::new(RefCell::new(values)))
+    }
+}
+
+impl Display for Enum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug)]
+pub struct EnumVariant {
+    // The enum owns its variants, a strong reference back would leak both
+    enum_ref: Weak<Enum>,
+    pub name: String,
+    pub ordinal: usize,
+}
+
+impl EnumVariant {
+    pub fn get(&self, name: &Token) -> Result<Value> {
+        match name.lexeme.as_str() {
+            "name" => Ok(Value::String(self.name.clone())),
+            "ordinal" => Ok(Value::Number(self.ordinal as f64)),
+            _ => Exception::runtime_error(
+                name.clone(),
+                format!("Undefined property '{}'.", name.lexeme),
+            ),
+        }
+    }
+
+    pub fn belongs_to(&self, other: &EnumRef) -> bool {
+        std::ptr::eq(self.enum_ref.as_ptr(), Rc::as_ptr(other))
+    }
+}
+
+impl Display for EnumVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let enum_name = match self.enum_ref.upgrade() {
+            Some(enum_ref) => enum_ref.name.clone(),
+            None => "<enum>".into(),
+        };
+        write!(f, "{}.{}", enum_name, self.name)
+    }
+}
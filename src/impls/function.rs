@@ -1,40 +1,143 @@
+use std::rc::Rc;
+
 use crate::{
     environment::{EnvRef, Environment},
     interpreter::Interpreter,
-    syntax::{stmt::Stmt, value::Value},
+    syntax::{stmt::Stmt, token::Token, value::Value},
     Exception,
 };
 
-use super::{callable::Callable, class::ClassInstanceRef};
+use super::{
+    callable::Callable,
+    class::{ClassInstanceRef, ClassRef},
+    generator::Generator,
+};
+
+// Natives receive the call's closing parenthesis to report runtime errors
+pub type NativeFn = fn(&mut Interpreter, &Token, Vec<Value>) -> Result<Value, Exception>;
 
 #[derive(Debug, Clone)]
 pub struct NativeFunction {
     pub arity: usize,
-    pub callable: fn(&mut Interpreter, Vec<Value>) -> Value,
+    pub callable: NativeFn,
+    this: Option<Box<Value>>,
+}
+
+impl NativeFunction {
+    pub fn new(arity: usize, callable: NativeFn) -> NativeFunction {
+        NativeFunction {
+            arity,
+            callable,
+            this: None,
+        }
+    }
+
+    // A bound native receives `this` as its first argument
+    pub fn bind(&self, this: Value) -> NativeFunction {
+        NativeFunction {
+            arity: self.arity,
+            callable: self.callable,
+            this: Some(Box::new(this)),
+        }
+    }
+
+    // Bound natives are only the same when they're bound to the same value
+    pub fn is_same(&self, other: &NativeFunction) -> bool {
+        if !std::ptr::fn_addr_eq(self.callable, other.callable) {
+            return false;
+        }
+
+        // References are compared by identity, dates by value, like `==` does
+        match (self.this.as_deref(), other.this.as_deref()) {
+            (Some(left), Some(right)) => Interpreter::is_equal(left, right),
+            (None, None) => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Function {
-    pub declaration: Stmt,
+    // Shared between every copy of the function, so it also works as its identity
+    pub declaration: Rc<Stmt>,
     pub closure: EnvRef,
     is_initializer: bool,
+    // Calling a function whose body yields returns a generator
+    is_generator: bool,
+    this: Option<Box<Value>>,
 }
 
 impl Function {
     pub fn new(declaration: Stmt, closure: EnvRef, is_initializer: bool) -> Function {
+        let is_generator = match &declaration {
+            Stmt::Function { body, .. } => Stmt::yields(body),
+            _ => false,
+        };
+
         Function {
-            declaration,
+            declaration: Rc::new(declaration),
             closure,
             is_initializer,
+            is_generator,
+            this: None,
         }
     }
 
     pub fn bind(&self, instance: ClassInstanceRef) -> Function {
+        self.bind_this(Value::ClassInstance(instance))
+    }
+
+    // Static methods are bound to the class they were accessed through
+    pub fn bind_class(&self, class: ClassRef) -> Function {
+        self.bind_this(Value::Class(class))
+    }
+
+    pub fn with_closure(&self, closure: EnvRef) -> Function {
+        Function {
+            declaration: Rc::clone(&self.declaration),
+            closure,
+            is_initializer: self.is_initializer,
+            is_generator: self.is_generator,
+            this: None,
+        }
+    }
+
+    fn bind_this(&self, this: Value) -> Function {
         let env = Environment::new_local(&self.closure);
-        env.borrow_mut()
-            .define("this".into(), Value::ClassInstance(instance));
+        env.borrow_mut().define("this".into(), this.clone());
 
-        Function::new(self.declaration.clone(), env, self.is_initializer)
+        Function {
+            declaration: Rc::clone(&self.declaration),
+            closure: env,
+            is_initializer: self.is_initializer,
+            is_generator: self.is_generator,
+            this: Some(Box::new(this)),
+        }
+    }
+
+    pub fn name(&self) -> &Token {
+        match self.declaration.as_ref() {
+            Stmt::Function { name, .. } => name,
+            _ => panic!("Function was not initalized with a function declaration!"),
+        }
+    }
+
+    // Two functions are the same when they come from the same declaration and
+    // capture the same scope. Binding creates a new scope on every access, so
+    // bound methods are compared by the instance they are bound to instead.
+    pub fn is_same(&self, other: &Function) -> bool {
+        if !Rc::ptr_eq(&self.declaration, &other.declaration) {
+            return false;
+        }
+
+        match (self.this.as_deref(), other.this.as_deref()) {
+            (Some(Value::ClassInstance(left)), Some(Value::ClassInstance(right))) => {
+                Rc::ptr_eq(left, right)
+            }
+            (Some(Value::Class(left)), Some(Value::Class(right))) => Rc::ptr_eq(left, right),
+            (None, None) => Rc::ptr_eq(&self.closure, &other.closure),
+            _ => false,
+        }
     }
 }
 
@@ -47,44 +150,80 @@ impl Callable for NativeFunction {
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Value>,
+        paren: &Token,
     ) -> Result<Value, Exception> {
-        Ok((self.callable)(interpreter, arguments))
+        match &self.this {
+            Some(this) => {
+                let mut bound_arguments = vec![this.as_ref().clone()];
+                bound_arguments.extend(arguments);
+                (self.callable)(interpreter, paren, bound_arguments)
+            }
+            None => (self.callable)(interpreter, paren, arguments),
+        }
     }
 }
 
 impl Callable for Function {
     fn arity(&self) -> usize {
-        if let Stmt::Function { parameters, .. } = &self.declaration {
+        if let Stmt::Function { parameters, .. } = self.declaration.as_ref() {
             return parameters.len();
         }
         panic!("Function was not initalized with a function declaration!");
     }
 
+    // Tail calls made by the body are run here, one after another, instead of nesting
     fn call(
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Value>,
+        paren: &Token,
+    ) -> Result<Value, Exception> {
+        interpreter.enter_call(paren)?;
+
+        let mut result = self.run(interpreter, arguments);
+        while let Err(Exception::TailCall(function, arguments)) = result {
+            result = function.run(interpreter, arguments);
+        }
+
+        interpreter.exit_call();
+        result
+    }
+}
+
+impl Function {
+    fn run(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Value>,
     ) -> Result<Value, Exception> {
         let env = Environment::new_local(&self.closure);
 
         if let Stmt::Function {
-            parameters, body, ..
-        } = &self.declaration
+            name,
+            parameters,
+            body,
+        } = self.declaration.as_ref()
         {
             for (i, value) in arguments.iter().enumerate() {
                 env.borrow_mut()
                     .define(parameters.get(i).unwrap().lexeme.clone(), value.clone());
             }
 
+            // The body only starts running when the generator is resumed
+            if self.is_generator {
+                let generator = Generator::new(name.lexeme.clone(), body, env);
+                return Ok(Value::Generator(generator));
+            }
+
             if let Err(e) = interpreter.execute_block(body, env) {
                 return match e {
-                    Exception::RuntimeError(e) => Err(Exception::RuntimeError(e)),
                     Exception::Return(value) => {
                         if self.is_initializer {
                             return self.closure.borrow().get_at(0, &"this".into());
                         }
                         Ok(value)
                     }
+                    e => Err(e),
                 };
             }
         }
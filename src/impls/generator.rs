@@ -0,0 +1,323 @@
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+use crate::{
+    environment::{EnvRef, Environment},
+    interpreter::Interpreter,
+    syntax::{expr::Expr, stmt::Stmt, token::Token, value::Value},
+    Exception,
+};
+
+use super::{function::NativeFunction, iterator::ValueIterator};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+pub type GeneratorRef = Rc<RefCell<Generator>>;
+
+// ======================
+// Design note:
+// ======================
+// Calling a function evaluates its body on the Rust stack, so there's no way
+// to stop in the middle of it and come back later. Generators keep their own
+// stack of frames instead: every statement that may contain a `yield` (blocks,
+// ifs, loops, matches) is unrolled into frames, everything else (expressions,
+// declarations...) is executed by the interpreter as usual. `resume` runs the
+// frames until a `yield` is found, and picks up from there on the next call.
+
+// Statements are cloned into frames once, when the enclosing statement is entered
+enum Frame {
+    Statements {
+        statements: Rc<Vec<Stmt>>,
+        index: usize,
+        env: EnvRef,
+    },
+    While {
+        condition: Expr,
+        body: Body,
+        env: EnvRef,
+    },
+    ForIn {
+        name: Token,
+        keyword: Token,
+        iterator: ValueIterator,
+        body: Body,
+        env: EnvRef,
+    },
+}
+
+// Loop bodies are run many times, so they're only cloned once
+struct Body {
+    statements: Rc<Vec<Stmt>>,
+    // Blocks get a new scope every time they run
+    is_block: bool,
+}
+
+impl Body {
+    fn new(stmt: &Stmt) -> Body {
+        match stmt {
+            Stmt::Block { statements } => Body {
+                statements: Rc::new(statements.clone()),
+                is_block: true,
+            },
+            _ => Body {
+                statements: Rc::new(vec![stmt.clone()]),
+                is_block: false,
+            },
+        }
+    }
+
+    fn frame(&self, env: EnvRef) -> Frame {
+        Frame::Statements {
+            statements: Rc::clone(&self.statements),
+            index: 0,
+            env: if self.is_block {
+                Environment::new_local(&env)
+            } else {
+                env
+            },
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum State {
+    Suspended,
+    Running,
+    Done,
+}
+
+pub struct Generator {
+    name: String,
+    frames: Vec<Frame>,
+    state: State,
+}
+
+impl Generator {
+    pub fn new(name: String, body: &[Stmt], env: EnvRef) -> GeneratorRef {
+        let frame = Frame::Statements {
+            statements: Rc::new(body.to_vec()),
+            index: 0,
+            env,
+        };
+
+        Rc::new(RefCell::new(Generator {
+            name,
+            frames: vec![frame],
+            state: State::Suspended,
+        }))
+    }
+
+    // Generators only have the `next()` method
+    pub fn get(name: &Token, generator: GeneratorRef) -> Result<Value> {
+        if name.lexeme == "next" {
+            let next = NativeFunction::new(0, |interpreter, paren, args| match &args[0] {
+                Value::Generator(generator) => {
+                    Ok(Generator::resume(generator, interpreter, paren)?.unwrap_or(Value::Nil))
+                }
+                _ => panic!("next() is not bound to a generator!"),
+            });
+            return Ok(Value::NativeFunction(
+                next.bind(Value::Generator(generator)),
+            ));
+        }
+
+        Exception::runtime_error(
+            name.clone(),
+            format!("Undefined property '{}'.", name.lexeme),
+        )
+    }
+
+    // Runs the generator until its next `yield`, None means it's finished
+    pub fn resume(
+        generator: &GeneratorRef,
+        interpreter: &mut Interpreter,
+        token: &Token,
+    ) -> Result<Option<Value>> {
+        let mut frames = {
+            let mut generator = generator.borrow_mut();
+            match generator.state {
+                State::Done => return Ok(None),
+                State::Running => {
+                    return Exception::runtime_error(
+                        token.clone(),
+                        "Generator is already running.".into(),
+                    )
+                }
+                State::Suspended => (),
+            }
+            generator.state = State::Running;
+            std::mem::take(&mut generator.frames)
+        };
+
+        let result = Generator::run(&mut frames, interpreter);
+
+        let mut generator = generator.borrow_mut();
+        match result {
+            Ok(Some(value)) => {
+                generator.frames = frames;
+                generator.state = State::Suspended;
+                Ok(Some(value))
+            }
+            // Returning or failing finishes the generator for good
+            _ => {
+                generator.state = State::Done;
+                result
+            }
+        }
+    }
+
+    fn run(frames: &mut Vec<Frame>, interpreter: &mut Interpreter) -> Result<Option<Value>> {
+        while let Some(frame) = frames.last_mut() {
+            match frame {
+                Frame::Statements {
+                    statements,
+                    index,
+                    env,
+                } => {
+                    if *index >= statements.len() {
+                        frames.pop();
+                        continue;
+                    }
+
+                    let statements = Rc::clone(statements);
+                    let stmt = &statements[*index];
+                    let env = env.clone();
+                    *index += 1;
+
+                    match Generator::step(frames, interpreter, stmt, env)? {
+                        Step::Continue => (),
+                        Step::Yield(value) => return Ok(Some(value)),
+                        Step::Return => return Ok(None),
+                    }
+                }
+                Frame::While {
+                    condition,
+                    body,
+                    env,
+                } => {
+                    let value = interpreter.evaluate_in(condition, env.clone())?;
+                    if Interpreter::is_truthy(&value) {
+                        let frame = body.frame(env.clone());
+                        frames.push(frame);
+                    } else {
+                        frames.pop();
+                    }
+                }
+                Frame::ForIn {
+                    name,
+                    keyword,
+                    iterator,
+                    body,
+                    env,
+                } => match iterator.next(interpreter, keyword)? {
+                    Some(value) => {
+                        let loop_env = Environment::new_local(env);
+                        loop_env.borrow_mut().define(name.lexeme.clone(), value);
+                        let frame = body.frame(loop_env);
+                        frames.push(frame);
+                    }
+                    None => {
+                        frames.pop();
+                    }
+                },
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Statements that may contain a `yield` become frames, the rest run right away
+    fn step(
+        frames: &mut Vec<Frame>,
+        interpreter: &mut Interpreter,
+        stmt: &Stmt,
+        env: EnvRef,
+    ) -> Result<Step> {
+        match stmt {
+            Stmt::Yield { value, .. } => {
+                let value = interpreter.evaluate_in(value, env)?;
+                return Ok(Step::Yield(value));
+            }
+            Stmt::Return { .. } => return Ok(Step::Return),
+            Stmt::Block { statements } => frames.push(Frame::Statements {
+                statements: Rc::new(statements.clone()),
+                index: 0,
+                env: Environment::new_local(&env),
+            }),
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let value = interpreter.evaluate_in(condition, env.clone())?;
+                let branch = if Interpreter::is_truthy(&value) {
+                    Some(then_branch)
+                } else {
+                    else_branch.as_ref()
+                };
+
+                if let Some(branch) = branch {
+                    frames.push(Body::new(branch).frame(env));
+                }
+            }
+            Stmt::While { condition, body } => frames.push(Frame::While {
+                condition: condition.clone(),
+                body: Body::new(body),
+                env,
+            }),
+            Stmt::ForIn {
+                name,
+                keyword,
+                iterable,
+                body,
+            } => {
+                let iterable = interpreter.evaluate_in(iterable, env.clone())?;
+                let iterator = ValueIterator::new(interpreter, iterable, keyword)?;
+                frames.push(Frame::ForIn {
+                    name: name.clone(),
+                    keyword: keyword.clone(),
+                    iterator,
+                    body: Body::new(body),
+                    env,
+                });
+            }
+            Stmt::Match {
+                keyword,
+                subject,
+                cases,
+            } => {
+                let subject = interpreter.evaluate_in(subject, env.clone())?;
+                if let Some((case, case_env)) =
+                    interpreter.select_case(keyword, &subject, cases, &env)?
+                {
+                    frames.push(Body::new(&case.body).frame(case_env));
+                }
+            }
+            _ => interpreter.execute_in(stmt, env)?,
+        }
+
+        Ok(Step::Continue)
+    }
+}
+
+enum Step {
+    Continue,
+    Yield(Value),
+    Return,
+}
+
+impl Display for Generator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<generator {}>", self.name)
+    }
+}
+
+// Frames are an implementation detail, printing them wouldn't help
+impl Debug for Generator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Generator({})", self.name)
+    }
+}
@@ -0,0 +1,128 @@
+use std::vec::IntoIter;
+
+use crate::{
+    impls::generator::{Generator, GeneratorRef},
+    interpreter::Interpreter,
+    syntax::{
+        token::Token,
+        value::{ListRef, Value},
+    },
+    Exception,
+};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+// What `for (var x in iterable)` walks over
+pub enum ValueIterator {
+    // Lists are read by position, so elements appended while iterating are visited too
+    List(ListRef, usize),
+    // Map keys and string characters are collected up front
+    Values(IntoIter<Value>),
+    Range {
+        next: f64,
+        end: f64,
+        inclusive: bool,
+    },
+    Generator(GeneratorRef),
+    // Any instance with a `next()` method, iteration ends when it returns nil
+    Instance(Value),
+}
+
+impl ValueIterator {
+    pub fn new(
+        interpreter: &mut Interpreter,
+        iterable: Value,
+        token: &Token,
+    ) -> Result<ValueIterator> {
+        match iterable {
+            Value::List(list) => Ok(ValueIterator::List(list, 0)),
+            Value::Generator(generator) => Ok(ValueIterator::Generator(generator)),
+            Value::Map(map) => {
+                let keys: Vec<Value> = map.borrow().keys().into_iter().map(Value::String).collect();
+                Ok(ValueIterator::Values(keys.into_iter()))
+            }
+            Value::String(string) => {
+                let chars: Vec<Value> = string
+                    .chars()
+                    .map(|c| Value::String(c.to_string()))
+                    .collect();
+                Ok(ValueIterator::Values(chars.into_iter()))
+            }
+            Value::Range(range) => Ok(ValueIterator::Range {
+                next: range.start,
+                end: range.end,
+                inclusive: range.inclusive,
+            }),
+            Value::ClassInstance(ref instance) => {
+                if let Some(iterator) =
+                    interpreter.call_special_method(&iterable, "iter", vec![], token)?
+                {
+                    // `iter()` is usually a generator method
+                    return match iterator {
+                        Value::Generator(generator) => Ok(ValueIterator::Generator(generator)),
+                        iterator => Ok(ValueIterator::Instance(iterator)),
+                    };
+                }
+
+                // Iterators are iterable themselves
+                if instance
+                    .borrow()
+                    .class
+                    .find_method(&"next".into())
+                    .is_some()
+                {
+                    return Ok(ValueIterator::Instance(iterable));
+                }
+
+                Exception::runtime_error(
+                    token.clone(),
+                    "Instances must have an 'iter()' or a 'next()' method to be iterated.".into(),
+                )
+            }
+            value => Exception::runtime_error(
+                token.clone(),
+                format!("Can't iterate over {}.", value.type_name()),
+            ),
+        }
+    }
+
+    pub fn next(&mut self, interpreter: &mut Interpreter, token: &Token) -> Result<Option<Value>> {
+        match self {
+            ValueIterator::List(list, position) => {
+                let value = list.borrow().get(*position).cloned();
+                *position += 1;
+                Ok(value)
+            }
+            ValueIterator::Values(values) => Ok(values.next()),
+            ValueIterator::Generator(generator) => Generator::resume(generator, interpreter, token),
+            ValueIterator::Range {
+                next,
+                end,
+                inclusive,
+            } => {
+                let is_done = if *inclusive {
+                    *next > *end
+                } else {
+                    *next >= *end
+                };
+                if is_done {
+                    return Ok(None);
+                }
+
+                let value = *next;
+                *next += 1.0;
+                Ok(Some(Value::Number(value)))
+            }
+            ValueIterator::Instance(iterator) => {
+                match interpreter.call_special_method(iterator, "next", vec![], token)? {
+                    Some(Value::Nil) => Ok(None),
+                    Some(value) => Ok(Some(value)),
+                    None => Exception::runtime_error(
+                        token.clone(),
+                        "Iterators must have a 'next()' method.".into(),
+                    ),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::syntax::value::Value;
+
+// Maps are shared and mutable, like lists
+pub type MapRef = Rc<RefCell<Map>>;
+
+// Keys are strings, they're kept in insertion order so iterating
+// and printing a map is deterministic
+#[derive(Debug, Default)]
+pub struct Map {
+    keys: Vec<String>,
+    values: HashMap<String, Value>,
+}
+
+impl Map {
+    pub fn new() -> MapRef {
+        Rc::new(RefCell::new(Map::default()))
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.values.get(key).cloned()
+    }
+
+    pub fn insert(&mut self, key: String, value: Value) {
+        if !self.values.contains_key(&key) {
+            self.keys.push(key.clone());
+        }
+        self.values.insert(key, value);
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    pub fn entries(&self) -> Vec<(String, Value)> {
+        self.keys
+            .iter()
+            .map(|key| (key.clone(), self.values[key].clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
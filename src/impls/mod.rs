@@ -1,3 +1,10 @@
 pub mod function;
 pub mod callable;
-pub mod class;
\ No newline at end of file
+pub mod class;
+pub mod date;
+pub mod enums;
+pub mod generator;
+pub mod iterator;
+pub mod map;
+pub mod namespace;
+pub mod traits;
\ No newline at end of file
@@ -0,0 +1,39 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+// Natives grouped under a name, e.g. `json.parse`
+pub type NamespaceRef = Rc<Namespace>;
+
+#[derive(Debug)]
+pub struct Namespace {
+    name: String,
+    members: HashMap<String, Value>,
+}
+
+impl Namespace {
+    pub fn new(name: String, members: HashMap<String, Value>) -> NamespaceRef {
+        Rc::new(Namespace { name, members })
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value> {
+        match self.members.get(&name.lexeme) {
+            Some(member) => Ok(member.clone()),
+            None => Exception::runtime_error(
+                name.clone(),
+                format!("Undefined member '{}' in '{}'.", name.lexeme, self.name),
+            ),
+        }
+    }
+}
+
+impl Display for Namespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<namespace {}>", self.name)
+    }
+}
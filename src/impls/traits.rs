@@ -0,0 +1,61 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    environment::{EnvRef, Environment},
+    syntax::value::Value,
+};
+
+use super::{class::ClassRef, function::Function};
+
+// Traits are reference values too, a class keeps track of the ones mixed into it
+pub type TraitRef = Rc<Trait>;
+
+#[derive(Debug)]
+pub struct Trait {
+    pub abstract_methods: Vec<String>,
+    pub name: String,
+    methods: HashMap<String, Function>,
+    closure: EnvRef,
+}
+
+impl Trait {
+    pub fn new(
+        abstract_methods: Vec<String>,
+        name: String,
+        methods: HashMap<String, Function>,
+        closure: EnvRef,
+    ) -> TraitRef {
+        Rc::new(Trait {
+            abstract_methods,
+            name,
+            methods,
+            closure,
+        })
+    }
+
+    // Trait methods are copied into every class that mixes the trait in.
+    // Each copy closes over a new scope holding the `super` of that class, the
+    // returned scope must also receive the class itself as "#class" once it's created.
+    pub fn mix_into(&self, super_class: &Option<ClassRef>) -> (EnvRef, HashMap<String, Function>) {
+        let env = Environment::new_local(&self.closure);
+        let super_class = match super_class {
+            Some(super_class) => Value::Class(super_class.clone()),
+            None => Value::Nil,
+        };
+        env.borrow_mut().define("super".into(), super_class);
+
+        let methods = self
+            .methods
+            .iter()
+            .map(|(name, method)| (name.clone(), method.with_closure(env.clone())))
+            .collect();
+
+        (env, methods)
+    }
+}
+
+impl Display for Trait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
@@ -1,83 +1,194 @@
-use std::{
-    collections::HashMap,
-    time::{SystemTime, UNIX_EPOCH},
-};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
 
 use crate::{
     environment::{EnvRef, Environment},
     impls::{
         callable::Callable,
-        class::Class,
-        function::{Function, NativeFunction},
+        class::{Class, ClassInstanceRef, ClassMembers, ClassRef},
+        enums::Enum,
+        function::Function,
+        generator::Generator,
+        iterator::ValueIterator,
+        map::Map,
+        traits::{Trait, TraitRef},
     },
+    limits::{Budget, Limit, Limits},
+    natives::Module,
+    output::{Output, OutputRef, Stdout, Stream},
     syntax::{
         expr::{self, Expr},
-        stmt::{self, Stmt},
+        pattern::{MatchCase, Pattern, Target},
+        stmt::{self, ClassDeclaration, Stmt},
         token::{Literal, Token},
         token_type::TokenType,
-        value::Value,
+        value::{Range, Value},
     },
+    utils::random::Random,
     Exception,
 };
 
 type Result<T> = std::result::Result<T, Exception>;
 
+// A nested Lox call takes up to 24 KiB of native stack in debug builds and up
+// to 16 KiB in release builds (calls inside loops and blocks), so a default
+// 8 MiB thread stack overflows after about 340 and 520 of them. 200 and 400
+// leave room for the host and for natives calling back into Lox.
+pub const MAX_CALL_DEPTH: usize = if cfg!(debug_assertions) { 200 } else { 400 };
+
+// Printing recurses once per nested list or map
+const MAX_PRINT_NESTING: usize = 512;
+
 pub struct Interpreter {
     pub globals: EnvRef,
     locals: HashMap<Expr, usize>,
     env: EnvRef,
+    // Nested Lox function calls, tail calls don't count
+    call_depth: usize,
+    max_call_depth: usize,
+    budget: Budget,
+    exceeded_limit: Option<Limit>,
+    exit_code: Option<i32>,
+    output: OutputRef,
+    random: Random,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        let globals = Environment::new_global();
+        Self::with_modules(Module::ALL)
+    }
 
-        globals.borrow_mut().define(
-            "print".into(),
-            Value::NativeFunction(NativeFunction {
-                arity: 1,
-                callable: |_, args| {
-                    let value = args.get(0).unwrap().clone();
-                    let value = Interpreter::stringfy(&value);
-                    println!("{}", value);
-                    Value::Nil
-                },
-            }),
-        );
+    // Only the given native modules are defined, e.g. `Module::PURE` for sandboxed scripts
+    pub fn with_modules(modules: &[Module]) -> Self {
+        let globals = Environment::new_global();
 
-        globals.borrow_mut().define(
-            "clock".into(),
-            Value::NativeFunction(NativeFunction {
-                arity: 0,
-                callable: |_, _| {
-                    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-                    Value::Number(timestamp.as_millis() as f64)
-                },
-            }),
-        );
+        for module in modules {
+            module.register(&globals);
+        }
 
         Self {
             env: globals.clone(),
             globals,
             locals: HashMap::new(),
+            call_depth: 0,
+            max_call_depth: MAX_CALL_DEPTH,
+            budget: Budget::default(),
+            exceeded_limit: None,
+            exit_code: None,
+            output: Rc::new(RefCell::new(Stdout)),
+            random: Random::from_time(),
         }
     }
 
+    // Where `print` and error messages are written, stdout and stderr by default
+    pub fn set_output(&mut self, output: impl Output + 'static) {
+        self.output = Rc::new(RefCell::new(output));
+    }
+
+    pub fn output(&self) -> OutputRef {
+        Rc::clone(&self.output)
+    }
+
+    pub fn random(&mut self) -> &mut Random {
+        &mut self.random
+    }
+
+    // Embedders can seed scripts too, to make them reproducible
+    pub fn seed(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+    }
+
+    // Sleeping past the time limit would let a script run for longer than allowed
+    pub fn sleep(&mut self, duration: Duration) -> Result<()> {
+        Ok(self.budget.sleep(duration)?)
+    }
+
+    pub fn print(&mut self, line: &str) {
+        self.output.borrow_mut().write(Stream::Print, line);
+    }
+
+    pub fn prompt(&mut self, text: &str) {
+        self.output.borrow_mut().write(Stream::Prompt, text);
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.budget.limits = limits;
+    }
+
+    // The limit that stopped the last `interpret` call, if any
+    pub fn exceeded_limit(&self) -> Option<&Limit> {
+        self.exceeded_limit.as_ref()
+    }
+
+    // Natives building big values should account for them here
+    pub fn allocate(&mut self, bytes: usize) -> Result<()> {
+        Ok(self.budget.allocate(bytes)?)
+    }
+
+    // Set when the last `interpret` call was stopped by `exit(code)`
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    // Embedders running scripts on smaller stacks (e.g. the 2 MiB of spawned
+    // threads) should lower the limit, bigger stacks can afford a higher one
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub fn enter_call(&mut self, paren: &Token) -> Result<()> {
+        if self.call_depth >= self.max_call_depth {
+            return Exception::runtime_error(
+                paren.clone(),
+                format!(
+                    "Stack overflow: maximum recursion depth {} exceeded.",
+                    self.max_call_depth
+                ),
+            );
+        }
+
+        self.call_depth += 1;
+        Ok(())
+    }
+
+    pub fn exit_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
     // List of statements == actual program
     pub fn interpret(&mut self, statements: Vec<Stmt>) {
+        self.budget.start();
+        self.exceeded_limit = None;
+        self.exit_code = None;
+
         for stmt in statements {
             match self.execute(&stmt) {
                 Ok(_) => (),
                 Err(e) => match e {
                     Exception::RuntimeError(e) => e.error(),
                     // This edge case (`return` keyword on top level code) is handled by the Resolver.
-                    Exception::Return(_) => (),
+                    Exception::Return(_) | Exception::TailCall(..) => (),
+                    Exception::LimitExceeded(limit) => {
+                        limit.error();
+                        self.exceeded_limit = Some(limit);
+                        return;
+                    }
+                    Exception::Exit(code) => {
+                        self.exit_code = Some(code);
+                        return;
+                    }
                 },
             }
         }
     }
 
     fn execute(&mut self, stmt: &Stmt) -> Result<()> {
+        self.budget.step()?;
         stmt::Visitor::visit_stmt(self, stmt)
     }
 
@@ -103,7 +214,7 @@ impl Interpreter {
         &mut self,
         class_name: &Token,
         super_class_expr: &Expr,
-    ) -> Result<Class> {
+    ) -> Result<ClassRef> {
         let evaluated = self.evaluate(super_class_expr)?;
         match evaluated {
             Value::Class(class) => Ok(class),
@@ -114,36 +225,79 @@ impl Interpreter {
         }
     }
 
-    fn visit_class_stmt(
-        &mut self,
-        name: &Token,
-        getters: &Vec<Stmt>,
-        methods: &Vec<Stmt>,
-        static_methods: &Vec<Stmt>,
-        super_class: &Option<Expr>,
-    ) -> Result<()> {
+    fn visit_class_stmt(&mut self, declaration: &ClassDeclaration) -> Result<()> {
+        let ClassDeclaration {
+            abstract_methods,
+            getters,
+            name,
+            methods,
+            private_methods,
+            setters,
+            static_methods,
+            super_class,
+            traits,
+        } = declaration;
+
         let super_class = match super_class {
-            Some(expr) => {
-                let class = self.evaluate_super_class(name, expr)?;
-                Some(Box::new(class))
-            }
+            Some(expr) => Some(self.evaluate_super_class(name, expr)?),
             None => None,
         };
 
-        self.env
-            .borrow_mut()
-            .define(name.lexeme.clone(), Value::Nil);
+        let mut class_traits = vec![];
+        for mixin in traits {
+            class_traits.push(self.evaluate_trait(name, mixin)?);
+        }
 
+        // Methods of every trait are copied into the class.
+        // Two traits providing the same method is an error, unless the class overrides it.
+        let mut trait_envs = vec![];
+        let mut class_methods = HashMap::new();
+        let mut provided_by: HashMap<String, String> = HashMap::new();
+        for mixin in &class_traits {
+            let (env, trait_methods) = mixin.mix_into(&super_class);
+            trait_envs.push(env);
+
+            for (method_name, method) in trait_methods {
+                let is_overridden = methods.iter().any(|method| match method {
+                    Stmt::Function { name, .. } => name.lexeme == method_name,
+                    _ => false,
+                });
+
+                if let Some(other) = provided_by.get(&method_name) {
+                    if !is_overridden {
+                        return Exception::runtime_error(
+                            name.clone(),
+                            format!(
+                                "Method '{}' is provided by both traits '{}' and '{}'.",
+                                method_name, other, mixin
+                            ),
+                        );
+                    }
+                }
+
+                provided_by.insert(method_name.clone(), mixin.name.clone());
+                class_methods.insert(method_name, method);
+            }
+        }
+
+        self.env.borrow_mut().declare(name, Value::Nil)?;
+
+        // Every class body gets its own scope holding the class itself (as "#class"),
+        // so methods can tell which class declared them when touching private members.
         let prev_env = self.env.clone();
+        let class_env = Environment::new_local(&self.env);
+        self.env = class_env.clone();
+
         if let Some(super_class) = &super_class {
             self.env = Environment::new_local(&self.env);
             self.env
                 .borrow_mut()
-                .define("super".into(), Value::Class(*super_class.clone()));
+                .define("super".into(), Value::Class(Rc::clone(super_class)));
         }
 
         let mut class_getters = HashMap::new();
-        let mut class_methods = HashMap::new();
+        let mut class_private_methods = HashMap::new();
+        let mut class_setters = HashMap::new();
         let mut class_static_methods = HashMap::new();
 
         for getter in getters {
@@ -156,6 +310,16 @@ impl Interpreter {
             };
         }
 
+        for setter in setters {
+            match setter {
+                Stmt::Function { name, .. } => {
+                    let function = Function::new(setter.clone(), self.env.clone(), false);
+                    class_setters.insert(name.lexeme.clone(), function);
+                }
+                _ => panic!("Stmt is not a setter!"),
+            };
+        }
+
         for static_method in static_methods {
             match static_method {
                 Stmt::Function { name, .. } => {
@@ -177,38 +341,247 @@ impl Interpreter {
             };
         }
 
+        for private_method in private_methods {
+            match private_method {
+                Stmt::Function { name, .. } => {
+                    let function = Function::new(private_method.clone(), self.env.clone(), false);
+                    class_private_methods.insert(name.lexeme.clone(), function);
+                }
+                _ => panic!("Stmt is not a private method!"),
+            };
+        }
+
+        let mut class_abstract_methods: Vec<String> = abstract_methods
+            .iter()
+            .map(|name| name.lexeme.clone())
+            .collect();
+        for mixin in &class_traits {
+            class_abstract_methods.extend(mixin.abstract_methods.iter().cloned());
+        }
+
+        let members = ClassMembers {
+            abstract_methods: class_abstract_methods,
+            getters: class_getters,
+            methods: class_methods,
+            private_methods: class_private_methods,
+            setters: class_setters,
+            static_methods: class_static_methods,
+        };
         let class = Class::new(
-            class_getters,
             name.lexeme.clone(),
-            class_methods,
-            class_static_methods,
+            members,
             super_class.clone(),
+            class_traits,
         );
 
-        if super_class.is_some() {
-            self.env = prev_env;
+        class_env
+            .borrow_mut()
+            .define("#class".into(), Value::Class(class.clone()));
+        for env in trait_envs {
+            env.borrow_mut()
+                .define("#class".into(), Value::Class(class.clone()));
         }
+        self.env = prev_env;
 
         self.env.borrow_mut().assign(name, Value::Class(class))?;
 
         Ok(())
     }
 
+    fn evaluate_trait(&mut self, class_name: &Token, trait_expr: &Expr) -> Result<TraitRef> {
+        let evaluated = self.evaluate(trait_expr)?;
+        match evaluated {
+            Value::Trait(mixin) => Ok(mixin),
+            _ => Exception::runtime_error(
+                class_name.clone(),
+                String::from("Can only mix traits into a class."),
+            ),
+        }
+    }
+
+    fn visit_trait_stmt(
+        &mut self,
+        name: &Token,
+        abstract_methods: &[Token],
+        methods: &Vec<Stmt>,
+    ) -> Result<()> {
+        let mut trait_methods = HashMap::new();
+
+        for method in methods {
+            match method {
+                Stmt::Function { name, .. } => {
+                    let function =
+                        Function::new(method.clone(), self.env.clone(), name.lexeme == "init");
+                    trait_methods.insert(name.lexeme.clone(), function);
+                }
+                _ => panic!("Stmt is not a method!"),
+            };
+        }
+
+        let mixin = Trait::new(
+            abstract_methods
+                .iter()
+                .map(|name| name.lexeme.clone())
+                .collect(),
+            name.lexeme.clone(),
+            trait_methods,
+            self.env.clone(),
+        );
+        self.env.borrow_mut().declare(name, Value::Trait(mixin))?;
+
+        Ok(())
+    }
+
+    fn visit_enum_stmt(&mut self, name: &Token, variants: &[Token]) -> Result<()> {
+        let enum_ref = Enum::new(name.lexeme.clone(), variants);
+        self.env.borrow_mut().declare(name, Value::Enum(enum_ref))?;
+
+        Ok(())
+    }
+
     fn evaluate(&mut self, expr: &Expr) -> Result<Value> {
-        expr::Visitor::visit_expr(self, expr)
+        self.budget.step()?;
+        let value = expr::Visitor::visit_expr(self, expr)?;
+
+        // Strings are copied every time they're evaluated
+        if let Value::String(string) = &value {
+            self.budget.allocate(string.len())?;
+        }
+        Ok(value)
     }
 
-    fn is_equal(a: &Value, b: &Value) -> bool {
+    pub fn is_equal(a: &Value, b: &Value) -> bool {
         match (a, b) {
             (Value::Nil, Value::Nil) => true,
             (Value::Number(left), Value::Number(right)) => left == right,
             (Value::String(left), Value::String(right)) => left == right,
             (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            // Reference types are compared by identity
+            (Value::Class(left), Value::Class(right)) => Rc::ptr_eq(left, right),
+            (Value::ClassInstance(left), Value::ClassInstance(right)) => Rc::ptr_eq(left, right),
+            (Value::Function(left), Value::Function(right)) => left.is_same(right),
+            (Value::NativeFunction(left), Value::NativeFunction(right)) => left.is_same(right),
+            (Value::Trait(left), Value::Trait(right)) => Rc::ptr_eq(left, right),
+            (Value::Enum(left), Value::Enum(right)) => Rc::ptr_eq(left, right),
+            (Value::EnumVariant(left), Value::EnumVariant(right)) => Rc::ptr_eq(left, right),
+            (Value::List(left), Value::List(right)) => Rc::ptr_eq(left, right),
+            (Value::Map(left), Value::Map(right)) => Rc::ptr_eq(left, right),
+            (Value::Namespace(left), Value::Namespace(right)) => Rc::ptr_eq(left, right),
+            (Value::Date(left), Value::Date(right)) => left == right,
+            (Value::Generator(left), Value::Generator(right)) => Rc::ptr_eq(left, right),
+            (Value::Range(left), Value::Range(right)) => {
+                left.start == right.start
+                    && left.end == right.end
+                    && left.inclusive == right.inclusive
+            }
             _ => false,
         }
     }
 
-    fn stringfy(value: &Value) -> String {
+    // Instances may override `==` and `!=` by declaring an `__eq__(other)`
+    // (or `equals(other)`) method
+    fn values_equal(&mut self, left: &Value, right: &Value, operator: &Token) -> Result<bool> {
+        for method in ["__eq__", "equals"] {
+            let args = vec![right.clone()];
+            if let Some(result) = self.call_special_method(left, method, args, operator)? {
+                return Ok(Interpreter::is_truthy(&result));
+            }
+        }
+
+        Ok(Interpreter::is_equal(left, right))
+    }
+
+    // Calls a special method (`__add__`, `__str__`...) when `value` is an instance
+    // whose class defines it. `None` means the regular behavior must be applied.
+    pub fn call_special_method(
+        &mut self,
+        value: &Value,
+        name: &str,
+        args: Vec<Value>,
+        token: &Token,
+    ) -> Result<Option<Value>> {
+        if let Value::ClassInstance(instance) = value {
+            let method = instance.borrow().class.find_method(&name.into());
+            if let Some(Value::Function(method)) = method {
+                let method = method.bind(instance.clone());
+                method.check_arity(args.len(), token)?;
+                return Ok(Some(method.call(self, args, token)?));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // `token` is blamed when the value is nested too deeply to print
+    pub fn stringfy(&mut self, value: &Value, token: &Token) -> Result<String> {
+        self.stringfy_nested(value, token, &mut vec![])
+    }
+
+    // `visiting` holds the lists and maps being printed, so one containing
+    // itself prints as `[...]` or `{...}`
+    fn stringfy_nested(
+        &mut self,
+        value: &Value,
+        token: &Token,
+        visiting: &mut Vec<*const ()>,
+    ) -> Result<String> {
+        if matches!(value, Value::List(_) | Value::Map(_)) && visiting.len() == MAX_PRINT_NESTING {
+            return Exception::runtime_error(
+                token.clone(),
+                format!("Can't print more than {} nested values.", MAX_PRINT_NESTING),
+            );
+        }
+
+        if let Value::ClassInstance(instance) = value {
+            let method = instance.borrow().class.find_method(&"__str__".into());
+            if let Some(Value::Function(method)) = method {
+                let method = method.bind(instance.clone());
+                method.check_arity(0, method.name())?;
+                return match method.call(self, vec![], method.name())? {
+                    Value::String(string) => Ok(string),
+                    value => self.stringfy(&value, method.name()),
+                };
+            }
+        }
+
+        // Elements may be instances with their own `__str__`
+        if let Value::List(list) = value {
+            let pointer = Rc::as_ptr(list) as *const ();
+            if visiting.contains(&pointer) {
+                return Ok("[...]".into());
+            }
+
+            visiting.push(pointer);
+            let elements = list.borrow().clone();
+            let mut strings = vec![];
+            for element in elements {
+                strings.push(self.stringfy_nested(&element, token, visiting)?);
+            }
+            visiting.pop();
+            return Ok(format!("[{}]", strings.join(", ")));
+        }
+
+        if let Value::Map(map) = value {
+            let pointer = Rc::as_ptr(map) as *const ();
+            if visiting.contains(&pointer) {
+                return Ok("{...}".into());
+            }
+
+            visiting.push(pointer);
+            let entries = map.borrow().entries();
+            let mut strings = vec![];
+            for (key, value) in entries {
+                let value = self.stringfy_nested(&value, token, visiting)?;
+                strings.push(format!("{}: {}", key, value));
+            }
+            visiting.pop();
+            return Ok(format!("{{{}}}", strings.join(", ")));
+        }
+
+        Ok(Interpreter::stringfy_value(value))
+    }
+
+    fn stringfy_value(value: &Value) -> String {
         match value {
             Value::Nil => "nil".into(),
             Value::Number(number) => {
@@ -222,15 +595,29 @@ impl Interpreter {
             Value::String(string) => string.clone(),
             Value::Boolean(value) => value.to_string(),
             Value::Function(f) => {
-                if let Stmt::Function { name, .. } = &f.declaration {
+                if let Stmt::Function { name, .. } = f.declaration.as_ref() {
                     return format!("<fn {}>", name.lexeme);
                 }
                 // In theory, it must never happen!
                 "<unknown function>".into()
             }
             Value::NativeFunction(_) => "<native fn>".into(),
+            Value::Generator(generator) => generator.borrow().to_string(),
             Value::Class(class) => class.to_string(),
+            Value::Trait(mixin) => mixin.to_string(),
+            Value::Namespace(namespace) => namespace.to_string(),
+            Value::Date(date) => date.to_string(),
+            Value::Enum(enum_ref) => enum_ref.to_string(),
+            Value::EnumVariant(variant) => variant.to_string(),
             Value::ClassInstance(class_instance) => class_instance.borrow().to_string(),
+            // Elements are formatted by `stringfy`, which guards against cycles
+            Value::List(_) | Value::Map(_) => unreachable!("formatted by stringfy"),
+            Value::Range(range) => format!(
+                "{}{}{}",
+                Interpreter::stringfy_value(&Value::Number(range.start)),
+                if range.inclusive { "..=" } else { ".." },
+                Interpreter::stringfy_value(&Value::Number(range.end))
+            ),
         }
     }
 
@@ -244,7 +631,236 @@ impl Interpreter {
             None => (),
         };
 
-        self.env.borrow_mut().define(name.lexeme.clone(), value);
+        self.env.borrow_mut().declare(name, value)
+    }
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+    ) -> Result<()> {
+        let iterable = self.evaluate(iterable)?;
+        let mut iterator = ValueIterator::new(self, iterable, keyword)?;
+
+        while let Some(value) = iterator.next(self, keyword)? {
+            let env = Environment::new_local(&self.env);
+            env.borrow_mut().define(name.lexeme.clone(), value);
+            self.execute_in(body, env)?;
+        }
+
+        Ok(())
+    }
+
+    // Cases are tried in order, only the first matching one runs
+    fn visit_match_stmt(
+        &mut self,
+        keyword: &Token,
+        subject: &Expr,
+        cases: &[MatchCase],
+    ) -> Result<()> {
+        let subject = self.evaluate(subject)?;
+
+        let env = self.env.clone();
+        match self.select_case(keyword, &subject, cases, &env)? {
+            Some((case, env)) => self.execute_in(&case.body, env),
+            None => Ok(()),
+        }
+    }
+
+    // The first case matching `subject`, along with the scope holding its bindings
+    pub fn select_case<'a>(
+        &mut self,
+        keyword: &Token,
+        subject: &Value,
+        cases: &'a [MatchCase],
+        enclosing: &EnvRef,
+    ) -> Result<Option<(&'a MatchCase, EnvRef)>> {
+        for case in cases {
+            for pattern in &case.patterns {
+                let env = Environment::new_local(enclosing);
+                if !self.match_pattern(keyword, pattern, subject, &env)? {
+                    continue;
+                }
+
+                if let Some(guard) = &case.guard {
+                    let passed = self.evaluate_in(guard, env.clone())?;
+                    if !Interpreter::is_truthy(&passed) {
+                        continue;
+                    }
+                }
+
+                return Ok(Some((case, env)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Variables bound by the pattern are defined in `env`
+    fn match_pattern(
+        &mut self,
+        keyword: &Token,
+        pattern: &Pattern,
+        value: &Value,
+        env: &EnvRef,
+    ) -> Result<bool> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Binding(name) => {
+                env.borrow_mut().define(name.lexeme.clone(), value.clone());
+                Ok(true)
+            }
+            Pattern::Value(expr) => {
+                let expected = self.evaluate_in(expr, env.clone())?;
+                self.values_equal(value, &expected, keyword)
+            }
+            Pattern::Instance {
+                class,
+                paren,
+                fields,
+            } => {
+                let class = match self.evaluate_in(class, env.clone())? {
+                    Value::Class(class) => class,
+                    _ => {
+                        return Exception::runtime_error(
+                            paren.clone(),
+                            "Only classes can be used in instance patterns.".into(),
+                        )
+                    }
+                };
+
+                let parameters = class.init_parameters();
+                if fields.len() > parameters.len() {
+                    return Exception::runtime_error(
+                        paren.clone(),
+                        format!(
+                            "Pattern has {} fields, but '{}' is initialized with {}.",
+                            fields.len(),
+                            class,
+                            parameters.len()
+                        ),
+                    );
+                }
+
+                let instance = match value {
+                    Value::ClassInstance(instance) if instance.borrow().class.inherits(&class) => {
+                        instance.clone()
+                    }
+                    _ => return Ok(false),
+                };
+
+                for (position, (field, name)) in fields.iter().zip(parameters).enumerate() {
+                    let field_value = instance.borrow().get_field(&name);
+                    let field_value = match field_value {
+                        Some(field_value) => field_value,
+                        // `init` stored its parameter under another name
+                        None => {
+                            return Exception::runtime_error(
+                                paren.clone(),
+                                format!(
+                                    "'{}' has no field '{}' for pattern position {}.",
+                                    class,
+                                    name,
+                                    position + 1
+                                ),
+                            )
+                        }
+                    };
+                    if !self.match_pattern(keyword, field, &field_value, env)? {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+        }
+    }
+
+    pub fn evaluate_in(&mut self, expr: &Expr, env: EnvRef) -> Result<Value> {
+        let previous = std::mem::replace(&mut self.env, env);
+        let result = self.evaluate(expr);
+        self.env = previous;
+        result
+    }
+
+    pub fn execute_in(&mut self, stmt: &Stmt, env: EnvRef) -> Result<()> {
+        let previous = std::mem::replace(&mut self.env, env);
+        let result = self.execute(stmt);
+        self.env = previous;
+        result
+    }
+
+    fn visit_const_stmt(&mut self, name: &Token, initializer: &Expr) -> Result<()> {
+        let value = self.evaluate(initializer)?;
+        self.env.borrow_mut().define_constant(name, value)
+    }
+
+    fn visit_destructure_stmt(&mut self, target: &Target, initializer: &Expr) -> Result<()> {
+        let value = self.evaluate(initializer)?;
+        let env = self.env.clone();
+        self.bind_target(target, value, &env)
+    }
+
+    // Missing list elements and map keys are bound to nil, missing fields are an error
+    fn bind_target(&mut self, target: &Target, value: Value, env: &EnvRef) -> Result<()> {
+        match target {
+            Target::Name(name) => env.borrow_mut().declare(name, value)?,
+            Target::List {
+                bracket,
+                elements,
+                rest,
+            } => {
+                let values = match value {
+                    Value::List(list) => list.borrow().clone(),
+                    value => {
+                        return Exception::runtime_error(
+                            bracket.clone(),
+                            format!(
+                                "Can't destructure {} with '[...]', expected a list.",
+                                value.type_name()
+                            ),
+                        )
+                    }
+                };
+
+                for (i, element) in elements.iter().enumerate() {
+                    let value = values.get(i).cloned().unwrap_or(Value::Nil);
+                    self.bind_target(element, value, env)?;
+                }
+
+                if let Some(rest) = rest {
+                    let remaining = values.into_iter().skip(elements.len()).collect();
+                    env.borrow_mut()
+                        .declare(rest, Value::List(Rc::new(RefCell::new(remaining))))?;
+                }
+            }
+            Target::Object { brace, names } => match value {
+                Value::ClassInstance(instance) => {
+                    for name in names {
+                        let value = instance.borrow().get(name, instance.clone(), self)?;
+                        env.borrow_mut().declare(name, value)?;
+                    }
+                }
+                Value::Map(map) => {
+                    for name in names {
+                        let value = map.borrow().get(&name.lexeme).unwrap_or(Value::Nil);
+                        env.borrow_mut().declare(name, value)?;
+                    }
+                }
+                value => {
+                    return Exception::runtime_error(
+                        brace.clone(),
+                        format!(
+                            "Can't destructure {} with '{{...}}', expected an instance or a map.",
+                            value.type_name()
+                        ),
+                    )
+                }
+            },
+        }
+
         Ok(())
     }
 
@@ -260,7 +876,7 @@ impl Interpreter {
 
         let distance = self.locals.get(expr);
         match distance {
-            Some(distance) => self.env.borrow_mut().assign_at(*distance, name, &value),
+            Some(distance) => self.env.borrow_mut().assign_at(*distance, name, &value)?,
             None => {
                 self.globals.borrow_mut().assign(name, value.clone())?;
             }
@@ -278,7 +894,7 @@ impl Interpreter {
         let function = Function::new(function_stmt.clone(), self.env.clone(), false);
         self.env
             .borrow_mut()
-            .define(name.lexeme.clone(), Value::Function(function));
+            .declare(name, Value::Function(function))?;
 
         Ok(())
     }
@@ -301,20 +917,64 @@ impl Interpreter {
 
     fn visit_return_stmt(&mut self, value: &Option<Expr>) -> Result<()> {
         match value {
+            // Calls in tail position don't grow the Rust stack
+            Some(Expr::Call {
+                callee,
+                paren,
+                arguments,
+                ..
+            }) => {
+                let (callee, arguments) = self.evaluate_call(callee, arguments)?;
+                match callee {
+                    Value::Function(function) => {
+                        function.check_arity(arguments.len(), paren)?;
+                        Err(Exception::TailCall(function, arguments))
+                    }
+                    callee => Err(Exception::Return(
+                        self.call_value(callee, arguments, paren)?,
+                    )),
+                }
+            }
             Some(expr) => Err(Exception::Return(self.evaluate(expr)?)),
             None => Err(Exception::Return(Value::Nil)),
         }
     }
 
+    fn operator_method(operator: &TokenType) -> Option<&'static str> {
+        match operator {
+            TokenType::Plus => Some("__add__"),
+            TokenType::Minus => Some("__sub__"),
+            TokenType::Star => Some("__mul__"),
+            TokenType::Slash => Some("__div__"),
+            TokenType::Less => Some("__lt__"),
+            TokenType::LessEqual => Some("__le__"),
+            TokenType::Greater => Some("__gt__"),
+            TokenType::GreaterEqual => Some("__ge__"),
+            _ => None,
+        }
+    }
+
     fn visit_binary_expr(&mut self, left: &Expr, operator: &Token, right: &Expr) -> Result<Value> {
         let left = self.evaluate(left)?;
         let right = self.evaluate(right)?;
 
+        // Operator overloading: the left operand decides
+        if let Some(method) = Interpreter::operator_method(&operator.token_type) {
+            let args = vec![right.clone()];
+            if let Some(result) = self.call_special_method(&left, method, args, operator)? {
+                return Ok(result);
+            }
+        }
+
         match operator.token_type {
             // Equality
             // --------------------------------------
-            TokenType::BangEqual => Ok(Value::Boolean(!Interpreter::is_equal(&left, &right))),
-            TokenType::EqualEqual => Ok(Value::Boolean(Interpreter::is_equal(&left, &right))),
+            TokenType::BangEqual => {
+                Ok(Value::Boolean(!self.values_equal(&left, &right, operator)?))
+            }
+            TokenType::EqualEqual => {
+                Ok(Value::Boolean(self.values_equal(&left, &right, operator)?))
+            }
             // Logic
             // --------------------------------------
             TokenType::Greater => match (left, right) {
@@ -325,6 +985,33 @@ impl Interpreter {
                 (Value::Number(left), Value::Number(right)) => Ok(Value::Boolean(left >= right)),
                 _ => Interpreter::number_operands_error(operator),
             },
+            TokenType::DotDot | TokenType::DotDotEqual => match (left, right) {
+                (Value::Number(start), Value::Number(end)) => Ok(Value::Range(Range {
+                    start,
+                    end,
+                    inclusive: operator.token_type == TokenType::DotDotEqual,
+                })),
+                _ => Interpreter::number_operands_error(operator),
+            },
+            // Instances are of their class, its super classes and every trait mixed into them
+            TokenType::Is => match (left, right) {
+                (Value::ClassInstance(instance), Value::Class(class)) => {
+                    Ok(Value::Boolean(instance.borrow().class.inherits(&class)))
+                }
+                (Value::ClassInstance(instance), Value::Trait(mixin)) => {
+                    Ok(Value::Boolean(instance.borrow().class.implements(&mixin)))
+                }
+                (Value::EnumVariant(variant), Value::Enum(enum_ref)) => {
+                    Ok(Value::Boolean(variant.belongs_to(&enum_ref)))
+                }
+                (_, Value::Class(_) | Value::Trait(_) | Value::Enum(_)) => {
+                    Ok(Value::Boolean(false))
+                }
+                _ => Exception::runtime_error(
+                    operator.clone(),
+                    "Right operand of 'is' must be a class, a trait or an enum.".into(),
+                ),
+            },
             TokenType::Less => match (left, right) {
                 (Value::Number(left), Value::Number(right)) => Ok(Value::Boolean(left < right)),
                 _ => Interpreter::number_operands_error(operator),
@@ -373,6 +1060,11 @@ impl Interpreter {
     }
 
     fn visit_call_expr(&mut self, callee: &Expr, paren: &Token, args: &Vec<Expr>) -> Result<Value> {
+        let (callee, evaluated_args) = self.evaluate_call(callee, args)?;
+        self.call_value(callee, evaluated_args, paren)
+    }
+
+    fn evaluate_call(&mut self, callee: &Expr, args: &Vec<Expr>) -> Result<(Value, Vec<Value>)> {
         let callee = self.evaluate(callee)?;
 
         let mut evaluated_args = vec![];
@@ -381,18 +1073,27 @@ impl Interpreter {
             evaluated_args.push(self.evaluate(arg)?);
         }
 
+        Ok((callee, evaluated_args))
+    }
+
+    fn call_value(
+        &mut self,
+        callee: Value,
+        evaluated_args: Vec<Value>,
+        paren: &Token,
+    ) -> Result<Value> {
         match callee {
             Value::Function(callee) => {
                 callee.check_arity(evaluated_args.len(), paren)?;
-                callee.call(self, evaluated_args)
+                callee.call(self, evaluated_args, paren)
             }
             Value::NativeFunction(callee) => {
                 callee.check_arity(evaluated_args.len(), paren)?;
-                callee.call(self, evaluated_args)
+                callee.call(self, evaluated_args, paren)
             }
             Value::Class(callee) => {
                 callee.check_arity(evaluated_args.len(), paren)?;
-                callee.call(self, evaluated_args)
+                callee.call(self, evaluated_args, paren)
             }
             _ => Exception::runtime_error(
                 paren.clone(),
@@ -401,15 +1102,103 @@ impl Interpreter {
         }
     }
 
+    // The class whose method is currently running, see `visit_class_stmt`
+    fn current_class(&self, name: &Token) -> Result<ClassRef> {
+        let class_token = Token::new(
+            TokenType::Identifier,
+            "#class".into(),
+            Literal::None,
+            name.line,
+        );
+        match self.env.borrow().get(&class_token) {
+            Ok(Value::Class(class)) => Ok(class),
+            _ => Exception::runtime_error(
+                name.clone(),
+                "Private members can only be used inside a class.".into(),
+            ),
+        }
+    }
+
+    fn private_instance(
+        &self,
+        name: &Token,
+        object: Value,
+    ) -> Result<(ClassRef, ClassInstanceRef)> {
+        let class = self.current_class(name)?;
+        match object {
+            Value::ClassInstance(instance) => {
+                if !instance.borrow().class.inherits(&class) {
+                    return Exception::runtime_error(
+                        name.clone(),
+                        format!(
+                            "Can't access private member '{}' from outside '{}'.",
+                            name.lexeme, class
+                        ),
+                    );
+                }
+                Ok((class, instance))
+            }
+            _ => Exception::runtime_error(
+                name.clone(),
+                "Only instances have private members.".into(),
+            ),
+        }
+    }
+
     fn visit_get_expr(&mut self, name: &Token, object: &Expr) -> Result<Value> {
         let object = self.evaluate(object)?;
+
+        if name.token_type == TokenType::PrivateIdentifier {
+            let (class, instance) = self.private_instance(name, object)?;
+            return instance
+                .borrow()
+                .get_private(name, &class, instance.clone());
+        }
+
         match object {
             Value::ClassInstance(instance) => instance.borrow().get(name, instance.clone(), self),
-            Value::Class(class) => class.get(name),
+            Value::Class(class) => class.get(name, class.clone()),
+            Value::Enum(enum_ref) => enum_ref.get(name, enum_ref.clone()),
+            Value::EnumVariant(variant) => variant.get(name),
+            Value::Generator(generator) => Generator::get(name, generator),
+            Value::Namespace(namespace) => namespace.get(name),
+            Value::Date(date) => date.get(name),
             _ => Exception::runtime_error(name.clone(), "Only instances have property".into()),
         }
     }
 
+    fn visit_list_expr(&mut self, elements: &[Expr]) -> Result<Value> {
+        let mut values = vec![];
+        for element in elements {
+            values.push(self.evaluate(element)?);
+        }
+
+        self.budget
+            .allocate(values.len() * std::mem::size_of::<Value>())?;
+        Ok(Value::List(Rc::new(RefCell::new(values))))
+    }
+
+    fn visit_map_expr(&mut self, brace: &Token, entries: &[(Expr, Expr)]) -> Result<Value> {
+        let map = Map::new();
+        for (key, value) in entries {
+            let key = match self.evaluate(key)? {
+                Value::String(key) => key,
+                _ => {
+                    return Exception::runtime_error(
+                        brace.clone(),
+                        "Map keys must be strings.".into(),
+                    )
+                }
+            };
+            let value = self.evaluate(value)?;
+            self.budget
+                .allocate(key.len() + std::mem::size_of::<Value>())?;
+            map.borrow_mut().insert(key, value);
+        }
+
+        Ok(Value::Map(map))
+    }
+
     fn visit_literal_expr(&self, expr: &Literal) -> Value {
         match expr {
             Literal::String(value) => Value::String(value.clone()),
@@ -442,12 +1231,35 @@ impl Interpreter {
 
     fn visit_set_expr(&mut self, name: &Token, object: &Expr, value: &Expr) -> Result<Value> {
         let object = self.evaluate(object)?;
+
+        if name.token_type == TokenType::PrivateIdentifier {
+            let (class, instance) = self.private_instance(name, object)?;
+            let value = self.evaluate(value)?;
+            instance.borrow_mut().set_private(name, &class, &value);
+            return Ok(value);
+        }
+
         match object {
             Value::ClassInstance(instance) => {
                 let value = self.evaluate(value)?;
+
+                // Setters take precedence over raw fields
+                let setter = instance.borrow().class.find_setter(name);
+                if let Some(Value::Function(setter)) = setter {
+                    setter
+                        .bind(instance.clone())
+                        .call(self, vec![value.clone()], name)?;
+                    return Ok(value);
+                }
+
                 instance.borrow_mut().set(name, &value)?;
                 Ok(value)
             }
+            Value::Class(class) => {
+                let value = self.evaluate(value)?;
+                class.set(name, &value)?;
+                Ok(value)
+            }
             _ => Exception::runtime_error(name.clone(), "Only instances have fields.".into()),
         }
     }
@@ -465,6 +1277,13 @@ impl Interpreter {
         
         let super_class = match super_class {
             Value::Class(super_class) => super_class,
+            // Trait methods mixed into a class without a superclass
+            Value::Nil => {
+                return Exception::runtime_error(
+                    method.clone(),
+                    "Can't use 'super' in a class with no superclass.".into(),
+                )
+            }
             _ => panic!("Expecteded superclass to be a class!"),
         };
 
@@ -473,12 +1292,15 @@ impl Interpreter {
             .borrow()
             // "this" is always right inside where "super" is stored
             .get_at(*distance - 1, &"this".into())?;
-        let object = match object {
-            Value::ClassInstance(instance) => instance,
-            _ => panic!("Expecteded 'this' to be a class instance!"),
+
+        // Inside static methods `this` is the class itself
+        let found = match &object {
+            Value::ClassInstance(_) => super_class.find_method(&method.lexeme),
+            Value::Class(_) => super_class.find_static_method(&method.lexeme),
+            _ => panic!("Expecteded 'this' to be a class instance or a class!"),
         };
 
-        let method = super_class.find_method(&method.lexeme).ok_or_else(|| {
+        let found = found.ok_or_else(|| {
             Exception::runtime_error::<()>(
                 method.clone(),
                 format!("Undefined property {}.", method.lexeme),
@@ -486,8 +1308,13 @@ impl Interpreter {
             .unwrap_err()
         })?;
 
-        match method {
-            Value::Function(method) => Ok(Value::Function(method.bind(object))),
+        match (found, object) {
+            (Value::Function(method), Value::ClassInstance(instance)) => {
+                Ok(Value::Function(method.bind(instance)))
+            }
+            (Value::Function(method), Value::Class(class)) => {
+                Ok(Value::Function(method.bind_class(class)))
+            }
             _ => panic!("Expected method to be a function!"),
         }
     }
@@ -496,13 +1323,119 @@ impl Interpreter {
         self.loopkup_variable(keyword, expr)
     }
 
+    fn visit_index_expr(&mut self, object: &Expr, bracket: &Token, index: &Expr) -> Result<Value> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+
+        match (&object, &index) {
+            (Value::List(list), Value::Number(number)) => {
+                let position = Interpreter::index_position(bracket, *number, list.borrow().len())?;
+                return Ok(list.borrow()[position].clone());
+            }
+            (Value::String(string), Value::Number(number)) => {
+                let chars: Vec<char> = string.chars().collect();
+                let position = Interpreter::index_position(bracket, *number, chars.len())?;
+                return Ok(Value::String(chars[position].to_string()));
+            }
+            (Value::Map(map), Value::String(key)) => {
+                return Ok(map.borrow().get(key).unwrap_or(Value::Nil));
+            }
+            (Value::List(_) | Value::String(_), _) => {
+                return Exception::runtime_error(bracket.clone(), "Index must be a number.".into());
+            }
+            (Value::Map(_), _) => {
+                return Exception::runtime_error(
+                    bracket.clone(),
+                    "Map keys must be strings.".into(),
+                );
+            }
+            _ => (),
+        }
+
+        match self.call_special_method(&object, "__index__", vec![index], bracket)? {
+            Some(value) => Ok(value),
+            None => Exception::runtime_error(
+                bracket.clone(),
+                "Only instances with an '__index__' method can be indexed.".into(),
+            ),
+        }
+    }
+
+    fn visit_set_index_expr(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> Result<Value> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let value = self.evaluate(value)?;
+
+        match (&object, &index) {
+            (Value::List(list), Value::Number(number)) => {
+                let position = Interpreter::index_position(bracket, *number, list.borrow().len())?;
+                self.budget.allocate(std::mem::size_of::<Value>())?;
+                list.borrow_mut()[position] = value.clone();
+                return Ok(value);
+            }
+            (Value::Map(map), Value::String(key)) => {
+                self.budget
+                    .allocate(key.len() + std::mem::size_of::<Value>())?;
+                map.borrow_mut().insert(key.clone(), value.clone());
+                return Ok(value);
+            }
+            (Value::List(_), _) => {
+                return Exception::runtime_error(bracket.clone(), "Index must be a number.".into());
+            }
+            (Value::Map(_), _) => {
+                return Exception::runtime_error(
+                    bracket.clone(),
+                    "Map keys must be strings.".into(),
+                );
+            }
+            _ => (),
+        }
+
+        let args = vec![index, value.clone()];
+        match self.call_special_method(&object, "__setindex__", args, bracket)? {
+            Some(_) => Ok(value),
+            None => Exception::runtime_error(
+                bracket.clone(),
+                "Only lists, maps and instances with a '__setindex__' method can be assigned by index."
+                    .into(),
+            ),
+        }
+    }
+
+    // Negative indexes count from the end
+    fn index_position(bracket: &Token, index: f64, len: usize) -> Result<usize> {
+        let position = if index < 0.0 {
+            index + len as f64
+        } else {
+            index
+        };
+
+        if position.fract() != 0.0 || position < 0.0 || position >= len as f64 {
+            return Exception::runtime_error(
+                bracket.clone(),
+                format!("Index {} out of range for length {}.", index, len),
+            );
+        }
+
+        Ok(position as usize)
+    }
+
     fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<Value> {
         let right = self.evaluate(right)?;
 
         match operator.token_type {
             TokenType::Minus => match right {
                 Value::Number(number) => Ok(Value::Number(-number)),
-                _ => Interpreter::number_operand_error(operator),
+                _ => match self.call_special_method(&right, "__neg__", vec![], operator)? {
+                    Some(value) => Ok(value),
+                    None => Interpreter::number_operand_error(operator),
+                },
             },
             TokenType::Bang => Ok(Value::Boolean(!Interpreter::is_truthy(&right))),
             _ => todo!(),
@@ -537,7 +1470,7 @@ impl Interpreter {
     }
 
     // Lox folows Ruby's rule: false and nil are false, everything else is true
-    fn is_truthy(value: &Value) -> bool {
+    pub fn is_truthy(value: &Value) -> bool {
         match value {
             Value::Boolean(value) => *value,
             Value::Nil => false,
@@ -550,14 +1483,19 @@ impl stmt::Visitor<Result<()>> for Interpreter {
     fn visit_stmt(&mut self, stmt: &Stmt) -> Result<()> {
         match stmt {
             Stmt::Expression(expr) => self.visit_expression_stmt(expr),
-            Stmt::Class {
-                getters,
+            Stmt::Class(declaration) => self.visit_class_stmt(declaration),
+            Stmt::Enum { name, variants } => self.visit_enum_stmt(name, variants),
+            Stmt::Trait {
+                abstract_methods,
                 name,
                 methods,
-                static_methods,
-                super_class,
-            } => self.visit_class_stmt(name, getters, methods, static_methods, super_class),
+            } => self.visit_trait_stmt(name, abstract_methods, methods),
             Stmt::Var { name, initializer } => self.visit_var_stmt(name, initializer),
+            Stmt::Const { name, initializer } => self.visit_const_stmt(name, initializer),
+            Stmt::Destructure {
+                target,
+                initializer,
+            } => self.visit_destructure_stmt(target, initializer),
             Stmt::Block { statements } => {
                 self.execute_block(statements, Environment::new_local(&self.env))
             }
@@ -566,9 +1504,25 @@ impl stmt::Visitor<Result<()>> for Interpreter {
                 then_branch,
                 else_branch,
             } => self.visit_if_stmt(condition, then_branch, else_branch),
+            Stmt::ForIn {
+                name,
+                keyword,
+                iterable,
+                body,
+            } => self.visit_for_in_stmt(name, keyword, iterable, body),
+            Stmt::Match {
+                keyword,
+                subject,
+                cases,
+            } => self.visit_match_stmt(keyword, subject, cases),
             Stmt::While { condition, body } => self.visit_while_stmt(condition, body),
             Stmt::Function { name, .. } => self.visit_function_stmt(name, stmt),
             Stmt::Return { value, .. } => self.visit_return_stmt(value),
+            // Generators run their bodies themselves, see `Generator::resume`
+            Stmt::Yield { keyword, .. } => Exception::runtime_error(
+                keyword.clone(),
+                "Can't yield outside of a generator.".into(),
+            ),
         }
     }
 }
@@ -583,6 +1537,8 @@ impl expr::Visitor<Result<Value>> for Interpreter {
                 ..
             } => self.visit_binary_expr(left, operator, right),
             Expr::Grouping { expression, .. } => self.evaluate(expression),
+            Expr::List { elements, .. } => self.visit_list_expr(elements),
+            Expr::Map { brace, entries, .. } => self.visit_map_expr(brace, entries),
             Expr::Literal { value, .. } => Ok(self.visit_literal_expr(value)),
             Expr::Unary {
                 operator, right, ..
@@ -602,14 +1558,84 @@ impl expr::Visitor<Result<Value>> for Interpreter {
                 ..
             } => self.visit_call_expr(callee, paren, arguments),
             Expr::Get { name, object, .. } => self.visit_get_expr(name, object),
+            Expr::Index {
+                object,
+                bracket,
+                index,
+                ..
+            } => self.visit_index_expr(object, bracket, index),
             Expr::Set {
                 name,
                 object,
                 value,
                 ..
             } => self.visit_set_expr(name, object, value),
+            Expr::SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+                ..
+            } => self.visit_set_index_expr(object, bracket, index, value),
             Expr::This { name, .. } => self.visit_this_expr(name, expr),
             Expr::Super { method, .. } => self.visit_super_expr(expr, method),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{output::Buffer, run};
+
+    // Recursion goes through a method, operator overloading, loops and blocks,
+    // which take more stack per call than a plain function
+    const RECURSION: &str = "
+        class Counter {
+            init(n) { this.n = n; }
+            __add__(other) { return Counter(this.n + other.n); }
+            down(n) {
+                if (n == 0) { return Counter(0); }
+                while (true) {
+                    for (var i = 0; i < 1; i = i + 1) {
+                        return Counter(1) + this.down(n - 1);
+                    }
+                }
+            }
+        }
+    ";
+
+    fn run_on_thread(source: String) -> (String, String) {
+        thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(move || {
+                let buffer = Buffer::new();
+                let mut interpreter = Interpreter::new();
+                interpreter.set_output(buffer.clone());
+                run(source, &mut interpreter);
+                (buffer.printed(), buffer.errors())
+            })
+            .unwrap()
+            .join()
+            .expect("The host thread must not overflow its stack")
+    }
+
+    #[test]
+    fn default_call_depth_fits_a_default_stack() {
+        let depth = MAX_CALL_DEPTH - 5;
+        let source = format!("{} print(Counter(0).down({}).n);", RECURSION, depth);
+        assert_eq!(run_on_thread(source), (format!("{}\n", depth), "".into()));
+
+        let source = format!("{} Counter(0).down({});", RECURSION, MAX_CALL_DEPTH);
+        let (_, errors) = run_on_thread(source);
+        assert_eq!(
+            errors,
+            format!(
+                "Error at line 9: Stack overflow: maximum recursion depth {} exceeded.\n",
+                MAX_CALL_DEPTH
+            )
+        );
+    }
+}
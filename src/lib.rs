@@ -1,6 +1,9 @@
 mod environment;
 mod impls;
 mod interpreter;
+mod limits;
+mod natives;
+mod output;
 mod parser;
 mod resolver;
 mod scanner;
@@ -8,23 +11,45 @@ mod syntax;
 mod utils;
 
 use std::{
+    cell::{Cell, RefCell},
     fs,
     io::{self, Write},
     process,
 };
 
-use interpreter::Interpreter;
+use impls::function::Function;
+pub use interpreter::{Interpreter, MAX_CALL_DEPTH};
+pub use limits::{Limit, Limits};
+pub use natives::Module;
+use output::OutputRef;
+pub use output::{Buffer, Output, Stdout, Stream};
 use parser::Parser;
 use resolver::Resolver;
 use scanner::Scanner;
 use syntax::{token::Token, token_type::TokenType, value::Value};
 
-static mut HAD_ERROR: bool = false;
-static mut HAD_RUNTIME_ERROR: bool = false;
+thread_local! {
+    static HAD_ERROR: Cell<bool> = const { Cell::new(false) };
+    static HAD_RUNTIME_ERROR: Cell<bool> = const { Cell::new(false) };
+    // The output of the interpreter `run` is using, errors are written there
+    static OUTPUT: RefCell<Option<OutputRef>> = const { RefCell::new(None) };
+}
 
-enum Exception {
+pub enum Exception {
     RuntimeError(RuntimeError),
     Return(Value),
+    // `return f(x);` unwinds the caller's frame before `f` runs, see `Function::call`
+    TailCall(Function, Vec<Value>),
+    // `exit(code)` unwinds everything, see `Interpreter::exit_code`
+    Exit(i32),
+    // Stops the whole script, embedders can tell it apart with `Interpreter::exceeded_limit`
+    LimitExceeded(Limit),
+}
+
+impl From<Limit> for Exception {
+    fn from(limit: Limit) -> Self {
+        Exception::LimitExceeded(limit)
+    }
 }
 
 impl Exception {
@@ -33,16 +58,19 @@ impl Exception {
     }
 }
 
-struct RuntimeError {
+pub struct RuntimeError {
     token: Token,
     message: String,
 }
 
 impl RuntimeError {
     fn error(&self) {
-        println!("Error at line {}: {}", self.token.line, self.message);
+        write_error(&format!(
+            "Error at line {}: {}",
+            self.token.line, self.message
+        ));
 
-        unsafe { HAD_RUNTIME_ERROR = true }
+        HAD_RUNTIME_ERROR.set(true);
     }
 }
 
@@ -54,9 +82,21 @@ pub fn error(line: usize, message: &str) {
 }
 
 pub fn report(line: usize, location: &str, message: &str) {
-    eprintln!("Error at line {} {}: {}", line, location, message);
-    // Aww men... Here goes unsafe :( Is there another way to make this?
-    unsafe { HAD_ERROR = true };
+    write_error(&format!("Error at line {} {}: {}", line, location, message));
+    HAD_ERROR.set(true);
+}
+
+// Warnings are only reported, they never stop the program from running
+pub fn warning(token: &Token, msg: &str) {
+    write_error(&format!("Warning at line {}: {}", token.line, msg));
+}
+
+// Outside of `run` there's no interpreter to ask, so errors go to stderr
+fn write_error(message: &str) {
+    OUTPUT.with(|output| match output.borrow().as_ref() {
+        Some(output) => output.borrow_mut().write(Stream::Error, message),
+        None => eprintln!("{}", message),
+    });
 }
 
 pub fn print_error(token: &Token, msg: &str) {
@@ -67,15 +107,29 @@ pub fn print_error(token: &Token, msg: &str) {
     }
 }
 
-pub fn run_file(path: &str) {
+// `args` are the command line arguments after the script's path
+pub fn run_file(path: &str, args: &[String]) {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            eprintln!("Couldn't read '{}': {}.", path, error);
+            process::exit(66)
+        }
+    };
+
     let mut interpreter = Interpreter::new();
-    let contents = fs::read_to_string(path).expect("File must be readable");
+    interpreter
+        .globals
+        .borrow_mut()
+        .define("args".into(), natives::string_list(args.to_vec()));
     run(contents, &mut interpreter);
 
-    unsafe {
-        if HAD_RUNTIME_ERROR {
-            process::exit(70)
-        }
+    if let Some(code) = interpreter.exit_code() {
+        process::exit(code)
+    }
+
+    if HAD_RUNTIME_ERROR.get() {
+        process::exit(70)
     }
 }
 
@@ -95,10 +149,18 @@ pub fn run_prompt() {
         }
 
         run(user_input.into(), &mut interpreter);
+
+        if let Some(code) = interpreter.exit_code() {
+            process::exit(code)
+        }
     }
 }
 
-fn run(source: String, interpreter: &mut Interpreter) {
+// Embedders can configure their own `Interpreter` (e.g. `set_max_call_depth`) and run with it
+pub fn run(source: String, interpreter: &mut Interpreter) {
+    HAD_RUNTIME_ERROR.set(false);
+    let previous = OUTPUT.with(|output| output.replace(Some(interpreter.output())));
+
     let mut scanner = Scanner::new(source);
     let tokens = scanner.scan_tokens();
 
@@ -109,14 +171,12 @@ fn run(source: String, interpreter: &mut Interpreter) {
         Ok(statements) => {
             resolver.resolve_block(&statements);
 
-            unsafe {
-                if HAD_RUNTIME_ERROR {
-                    return;
-                }
+            if !HAD_RUNTIME_ERROR.get() {
+                interpreter.interpret(statements);
             }
-
-            interpreter.interpret(statements);
         },
         Err(_) => (),
     }
+
+    OUTPUT.with(|output| output.replace(previous));
 }
@@ -0,0 +1,207 @@
+use std::{
+    fmt::Display,
+    thread,
+    time::{Duration, Instant},
+};
+
+// Deadlines are only checked every so many steps, reading the clock is slow
+const STEPS_PER_CLOCK_CHECK: u64 = 1024;
+
+// Budgets for running untrusted scripts, every limit is off by default.
+// They are counted from the start of each `Interpreter::interpret` call.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    // Statements executed plus expressions evaluated
+    pub max_steps: Option<u64>,
+    pub timeout: Option<Duration>,
+    // Bytes allocated by strings, lists, maps and instances. Nothing is
+    // given back when values are dropped, so this bounds the total work too.
+    pub max_memory: Option<usize>,
+}
+
+// The limit a script ran into, see `Exception::LimitExceeded`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Limit {
+    Steps(u64),
+    Time(Duration),
+    Memory(usize),
+}
+
+impl Limit {
+    // There's no token to point at, the script is stopped wherever it was
+    pub fn error(&self) {
+        crate::write_error(&format!("Error: {}", self));
+
+        crate::HAD_RUNTIME_ERROR.set(true);
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Steps(steps) => {
+                write!(f, "Execution limit exceeded: more than {} steps.", steps)
+            }
+            Limit::Time(timeout) => write!(
+                f,
+                "Execution limit exceeded: ran for more than {} ms.",
+                timeout.as_millis()
+            ),
+            Limit::Memory(bytes) => write!(
+                f,
+                "Execution limit exceeded: allocated more than {} bytes.",
+                bytes
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Budget {
+    pub limits: Limits,
+    steps: u64,
+    memory: usize,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub fn start(&mut self) {
+        self.steps = 0;
+        self.memory = 0;
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    pub fn step(&mut self) -> Result<(), Limit> {
+        self.steps += 1;
+
+        if let Some(max_steps) = self.limits.max_steps {
+            if self.steps > max_steps {
+                return Err(Limit::Steps(max_steps));
+            }
+        }
+
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout) {
+            if self.steps.is_multiple_of(STEPS_PER_CLOCK_CHECK) && Instant::now() > deadline {
+                return Err(Limit::Time(timeout));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn sleep(&self, duration: Duration) -> Result<(), Limit> {
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if duration > remaining {
+                thread::sleep(remaining);
+                return Err(Limit::Time(timeout));
+            }
+        }
+
+        thread::sleep(duration);
+        Ok(())
+    }
+
+    pub fn allocate(&mut self, bytes: usize) -> Result<(), Limit> {
+        self.memory = self.memory.saturating_add(bytes);
+
+        match self.limits.max_memory {
+            Some(max_memory) if self.memory > max_memory => Err(Limit::Memory(max_memory)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interpreter::Interpreter, parser::Parser, resolver::Resolver, scanner::Scanner};
+
+    fn interpret(source: &str, limits: Limits) -> Option<Limit> {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_limits(limits);
+
+        let tokens = Scanner::new(source.into()).scan_tokens();
+        let statements = Parser::new(tokens).parse().ok().unwrap();
+        Resolver::new(&mut interpreter).resolve_block(&statements);
+        interpreter.interpret(statements);
+
+        interpreter.exceeded_limit().cloned()
+    }
+
+    #[test]
+    fn steps() {
+        let limits = Limits {
+            max_steps: Some(1000),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            interpret("while (true) {}", limits.clone()),
+            Some(Limit::Steps(1000))
+        );
+        assert_eq!(interpret("var a = 1 + 2;", limits), None);
+    }
+
+    #[test]
+    fn time() {
+        let limits = Limits {
+            timeout: Some(Duration::from_millis(10)),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            interpret("while (true) {}", limits.clone()),
+            Some(Limit::Time(Duration::from_millis(10)))
+        );
+        assert_eq!(
+            interpret("time.sleep(60000);", limits),
+            Some(Limit::Time(Duration::from_millis(10)))
+        );
+    }
+
+    #[test]
+    fn memory() {
+        let limits = Limits {
+            max_memory: Some(1024),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            interpret(
+                "var s = \"ab\"; while (true) { s = s + s; }",
+                limits.clone()
+            ),
+            Some(Limit::Memory(1024))
+        );
+        assert_eq!(
+            interpret(
+                "while (true) { var list = [1, 2, 3]; list[0] = 2; }",
+                limits
+            ),
+            Some(Limit::Memory(1024))
+        );
+    }
+
+    #[test]
+    fn memory_index_assignment() {
+        // Fails on steps rather than hanging when writes aren't charged
+        let limits = Limits {
+            max_steps: Some(100_000),
+            max_memory: Some(1024),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            interpret(
+                "var map = {}; while (true) { map[\"key\"] = 1; }",
+                limits.clone()
+            ),
+            Some(Limit::Memory(1024))
+        );
+        assert_eq!(
+            interpret("var list = [1]; while (true) { list[0] = 2; }", limits),
+            Some(Limit::Memory(1024))
+        );
+    }
+}
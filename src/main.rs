@@ -1,4 +1,4 @@
-use std::{env, process};
+use std::env;
 
 use rlox::{run_file, run_prompt};
 
@@ -8,12 +8,7 @@ fn main() {
     match args.len() {
         // No arguments passed. Shows REPL.
         1 => run_prompt(),
-        // '.lox' file passed. Runs file's source code.
-        2 => run_file(args.get(1).unwrap().as_str()),
-        // Bad usage. Shows message.
-        _ => {
-            println!("Usage: jlox [script]");
-            process::exit(64)
-        }
+        // '.lox' file passed. Runs file's source code, the rest are its `args`.
+        _ => run_file(args[1].as_str(), &args[2..]),
     }
 }
@@ -0,0 +1,32 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{environment::EnvRef, syntax::value::Value, Exception};
+
+use super::define;
+
+pub fn register(globals: &EnvRef) {
+    define(globals, "print", 1, |interpreter, paren, args| {
+        let value = interpreter.stringfy(&args[0], paren)?;
+        interpreter.print(&value);
+        Ok(Value::Nil)
+    });
+
+    define(globals, "clock", 0, |_, _, _| {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Ok(Value::Number(timestamp.as_millis() as f64))
+    });
+
+    define(globals, "type", 1, |_, _, args| {
+        Ok(Value::String(args[0].type_name().into()))
+    });
+
+    define(globals, "len", 1, |_, paren, args| match &args[0] {
+        Value::String(string) => Ok(Value::Number(string.chars().count() as f64)),
+        Value::List(list) => Ok(Value::Number(list.borrow().len() as f64)),
+        Value::Map(map) => Ok(Value::Number(map.borrow().len() as f64)),
+        value => Exception::runtime_error(
+            paren.clone(),
+            format!("Can't get the length of {}.", value.type_name()),
+        ),
+    });
+}
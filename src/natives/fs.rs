@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    environment::EnvRef,
+    impls::function::NativeFn,
+    interpreter::Interpreter,
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+use super::{define_namespace, expect_string, string_list};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+pub fn register(globals: &EnvRef) {
+    define_namespace(
+        globals,
+        "fs",
+        &[
+            ("readFile", 1, read_file as NativeFn),
+            ("readLines", 1, read_lines),
+            ("writeFile", 2, write_file),
+            ("appendFile", 2, append_file),
+            ("exists", 1, exists),
+            ("listDir", 1, list_dir),
+            ("remove", 1, remove),
+        ],
+    );
+}
+
+fn read_file(interpreter: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(paren, &args[0], "readFile")?;
+    let contents = io_result(paren, "read", &path, fs::read_to_string(&path))?;
+    interpreter.allocate(contents.len())?;
+    Ok(Value::String(contents))
+}
+
+fn read_lines(interpreter: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(paren, &args[0], "readLines")?;
+    let contents = io_result(paren, "read", &path, fs::read_to_string(&path))?;
+    interpreter.allocate(contents.len())?;
+    Ok(string_list(contents.lines().map(String::from).collect()))
+}
+
+fn write_file(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(paren, &args[0], "writeFile")?;
+    let contents = expect_string(paren, &args[1], "writeFile")?;
+    io_result(paren, "write", &path, fs::write(&path, contents))?;
+    Ok(Value::Nil)
+}
+
+fn append_file(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(paren, &args[0], "appendFile")?;
+    let contents = expect_string(paren, &args[1], "appendFile")?;
+    let appended = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()));
+    io_result(paren, "write", &path, appended)?;
+    Ok(Value::Nil)
+}
+
+fn exists(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(paren, &args[0], "exists")?;
+    Ok(Value::Boolean(Path::new(&path).exists()))
+}
+
+// Entry names only, sorted so the order doesn't depend on the file system
+fn list_dir(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(paren, &args[0], "listDir")?;
+    let entries = fs::read_dir(&path).and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<String>>>()
+    });
+    let mut names = io_result(paren, "list", &path, entries)?;
+    names.sort();
+    Ok(string_list(names))
+}
+
+// Directories must be empty, nothing is ever removed recursively
+fn remove(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(paren, &args[0], "remove")?;
+    let removed = if Path::new(&path).is_dir() {
+        fs::remove_dir(&path)
+    } else {
+        fs::remove_file(&path)
+    };
+    io_result(paren, "remove", &path, removed)?;
+    Ok(Value::Nil)
+}
+
+fn io_result<T>(paren: &Token, action: &str, path: &str, result: io::Result<T>) -> Result<T> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(error) => Exception::runtime_error(
+            paren.clone(),
+            format!("Couldn't {} '{}': {}.", action, path, error),
+        ),
+    }
+}
@@ -0,0 +1,460 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    environment::EnvRef,
+    impls::{function::NativeFn, map::Map},
+    interpreter::Interpreter,
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+use super::{define_namespace, expect_string};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+// Deeper documents are rejected instead of overflowing the stack
+const MAX_NESTING: usize = 512;
+
+pub fn register(globals: &EnvRef) {
+    define_namespace(
+        globals,
+        "json",
+        &[
+            ("parse", 1, parse as NativeFn),
+            ("stringify", 2, stringify),
+        ],
+    );
+}
+
+fn parse(interpreter: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let text = expect_string(paren, &args[0], "parse")?;
+    interpreter.allocate(text.len())?;
+
+    let mut parser = JsonParser::new(&text);
+    match parser.document() {
+        Ok(value) => Ok(value),
+        Err(message) => Exception::runtime_error(
+            paren.clone(),
+            format!(
+                "Invalid JSON at line {}, column {}: {}.",
+                parser.line, parser.column, message
+            ),
+        ),
+    }
+}
+
+// `indent` is nil for compact output, a number of spaces or the string to indent with
+fn stringify(interpreter: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let indent = match &args[1] {
+        Value::Nil => String::new(),
+        Value::Number(spaces) if *spaces >= 0.0 && spaces.fract() == 0.0 => {
+            " ".repeat(*spaces as usize)
+        }
+        Value::String(indent) => indent.clone(),
+        value => {
+            return Exception::runtime_error(
+                paren.clone(),
+                format!(
+                    "stringify() expected nil, a number of spaces or a string as the indent, but got {}.",
+                    value.type_name()
+                ),
+            )
+        }
+    };
+
+    let mut writer = JsonWriter {
+        paren,
+        indent,
+        visiting: vec![],
+        json: String::new(),
+    };
+    writer.value(&args[0], 0)?;
+
+    interpreter.allocate(writer.json.len())?;
+    Ok(Value::String(writer.json))
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    current: usize,
+    // Where `current` is, for error messages
+    line: usize,
+    column: usize,
+    nesting: usize,
+}
+
+impl JsonParser {
+    fn new(text: &str) -> JsonParser {
+        JsonParser {
+            chars: text.chars().collect(),
+            current: 0,
+            line: 1,
+            column: 1,
+            nesting: 0,
+        }
+    }
+
+    fn document(&mut self) -> std::result::Result<Value, String> {
+        let value = self.value()?;
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => Ok(value),
+            Some(c) => Err(format!("unexpected '{}' after the value", c)),
+        }
+    }
+
+    fn value(&mut self) -> std::result::Result<Value, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => self.nested(JsonParser::object),
+            Some('[') => self.nested(JsonParser::array),
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", Value::Boolean(true)),
+            Some('f') => self.keyword("false", Value::Boolean(false)),
+            Some('n') => self.keyword("null", Value::Nil),
+            Some(c) => Err(format!("unexpected '{}'", c)),
+            None => Err("unexpected end of input".into()),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut JsonParser) -> std::result::Result<Value, String>,
+    ) -> std::result::Result<Value, String> {
+        if self.nesting == MAX_NESTING {
+            return Err(format!("more than {} nested values", MAX_NESTING));
+        }
+
+        self.nesting += 1;
+        let value = parse(self)?;
+        self.nesting -= 1;
+        Ok(value)
+    }
+
+    fn object(&mut self) -> std::result::Result<Value, String> {
+        self.advance();
+        let map = Map::new();
+
+        self.skip_whitespace();
+        if self.matches('}') {
+            return Ok(Value::Map(map));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err("expected a string as the key".into());
+            }
+            let key = self.string()?;
+
+            self.skip_whitespace();
+            if !self.matches(':') {
+                return Err("expected ':' after the key".into());
+            }
+
+            let value = self.value()?;
+            map.borrow_mut().insert(key, value);
+
+            self.skip_whitespace();
+            if self.matches('}') {
+                return Ok(Value::Map(map));
+            }
+            if !self.matches(',') {
+                return Err("expected ',' or '}' after the value".into());
+            }
+        }
+    }
+
+    fn array(&mut self) -> std::result::Result<Value, String> {
+        self.advance();
+        let mut values = vec![];
+
+        self.skip_whitespace();
+        if self.matches(']') {
+            return Ok(Value::List(Rc::new(RefCell::new(values))));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            self.skip_whitespace();
+            if self.matches(']') {
+                return Ok(Value::List(Rc::new(RefCell::new(values))));
+            }
+            if !self.matches(',') {
+                return Err("expected ',' or ']' after the value".into());
+            }
+        }
+    }
+
+    fn string(&mut self) -> std::result::Result<String, String> {
+        self.advance();
+        let mut string = String::new();
+
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.escape()?),
+                Some(c) if c < ' ' => return Err("control characters must be escaped".into()),
+                Some(c) => string.push(c),
+                None => return Err("unterminated string".into()),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> std::result::Result<char, String> {
+        match self.advance() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('/') => Ok('/'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('u') => {
+                let code = self.hex_code()?;
+                // Characters outside the basic plane are written as a surrogate pair
+                if (0xD800..0xDC00).contains(&code) {
+                    if !(self.matches('\\') && self.matches('u')) {
+                        return Err("expected a low surrogate".into());
+                    }
+                    let low = self.hex_code()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err("expected a low surrogate".into());
+                    }
+                    let code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    return char::from_u32(code).ok_or("invalid unicode escape".into());
+                }
+
+                char::from_u32(code).ok_or("invalid unicode escape".into())
+            }
+            Some(c) => Err(format!("invalid escape '\\{}'", c)),
+            None => Err("unterminated string".into()),
+        }
+    }
+
+    fn hex_code(&mut self) -> std::result::Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.advance().and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err("expected 4 hexadecimal digits".into()),
+            }
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> std::result::Result<Value, String> {
+        let start = self.current;
+
+        self.matches('-');
+        if !self.matches('0') && !self.digits() {
+            return Err("expected a digit".into());
+        }
+        if self.matches('.') && !self.digits() {
+            return Err("expected a digit after '.'".into());
+        }
+        if self.matches('e') || self.matches('E') {
+            let _ = self.matches('+') || self.matches('-');
+            if !self.digits() {
+                return Err("expected a digit in the exponent".into());
+            }
+        }
+
+        let number: String = self.chars[start..self.current].iter().collect();
+        number
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| format!("invalid number '{}'", number))
+    }
+
+    // Whether there was at least one digit
+    fn digits(&mut self) -> bool {
+        let start = self.current;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+        }
+        self.current > start
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> std::result::Result<Value, String> {
+        for expected in keyword.chars() {
+            if !self.matches(expected) {
+                return Err(format!("expected '{}'", keyword));
+            }
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn matches(&mut self, expected: char) -> bool {
+        if self.peek() != Some(expected) {
+            return false;
+        }
+
+        self.advance();
+        true
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += 1;
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+}
+
+struct JsonWriter<'a> {
+    paren: &'a Token,
+    indent: String,
+    // Lists, maps and instances being written, to reject the ones containing themselves
+    visiting: Vec<*const ()>,
+    json: String,
+}
+
+impl JsonWriter<'_> {
+    fn value(&mut self, value: &Value, depth: usize) -> Result<()> {
+        match value {
+            Value::Nil => self.json.push_str("null"),
+            Value::Boolean(boolean) => self.json.push_str(&boolean.to_string()),
+            Value::Number(number) if number.is_finite() => {
+                let number = number.to_string();
+                self.json
+                    .push_str(number.strip_suffix(".0").unwrap_or(&number));
+            }
+            Value::String(string) => self.string(string),
+            Value::List(list) => {
+                self.enter(Rc::as_ptr(list) as *const (), depth)?;
+                let elements = list.borrow().clone();
+                self.json.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    self.separator(i, depth + 1);
+                    self.value(element, depth + 1)?;
+                }
+                self.close(']', elements.is_empty(), depth);
+            }
+            Value::Map(map) => {
+                self.enter(Rc::as_ptr(map) as *const (), depth)?;
+                let entries = map.borrow().entries();
+                self.object(&entries, depth)?;
+            }
+            // Only the public fields, private ones stay private
+            Value::ClassInstance(instance) => {
+                self.enter(Rc::as_ptr(instance) as *const (), depth)?;
+                let entries: Vec<(String, Value)> = {
+                    let instance = instance.borrow();
+                    instance
+                        .field_names()
+                        .into_iter()
+                        .filter_map(|name| instance.get_field(&name).map(|value| (name, value)))
+                        .collect()
+                };
+                self.object(&entries, depth)?;
+            }
+            value => {
+                let description = match value {
+                    Value::Number(number) => number.to_string(),
+                    value => value.type_name().to_string(),
+                };
+                return Exception::runtime_error(
+                    self.paren.clone(),
+                    format!("Can't convert {} to JSON.", description),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn object(&mut self, entries: &[(String, Value)], depth: usize) -> Result<()> {
+        self.json.push('{');
+        for (i, (key, value)) in entries.iter().enumerate() {
+            self.separator(i, depth + 1);
+            self.string(key);
+            self.json
+                .push_str(if self.indent.is_empty() { ":" } else { ": " });
+            self.value(value, depth + 1)?;
+        }
+        self.close('}', entries.is_empty(), depth);
+        Ok(())
+    }
+
+    // `depth` counts the lists, maps and instances around the one entered
+    fn enter(&mut self, pointer: *const (), depth: usize) -> Result<()> {
+        if depth == MAX_NESTING {
+            return Exception::runtime_error(
+                self.paren.clone(),
+                format!("Can't convert more than {} nested values to JSON.", MAX_NESTING),
+            );
+        }
+
+        if self.visiting.contains(&pointer) {
+            return Exception::runtime_error(
+                self.paren.clone(),
+                "Can't convert a value that contains itself to JSON.".into(),
+            );
+        }
+
+        self.visiting.push(pointer);
+        Ok(())
+    }
+
+    fn separator(&mut self, index: usize, depth: usize) {
+        if index > 0 {
+            self.json.push(',');
+        }
+        self.new_line(depth);
+    }
+
+    fn close(&mut self, bracket: char, is_empty: bool, depth: usize) {
+        self.visiting.pop();
+        if !is_empty {
+            self.new_line(depth);
+        }
+        self.json.push(bracket);
+    }
+
+    fn new_line(&mut self, depth: usize) {
+        if !self.indent.is_empty() {
+            self.json.push('\n');
+            self.json.push_str(&self.indent.repeat(depth));
+        }
+    }
+
+    fn string(&mut self, string: &str) {
+        self.json.push('"');
+        for c in string.chars() {
+            match c {
+                '"' => self.json.push_str("\\\""),
+                '\\' => self.json.push_str("\\\\"),
+                '\n' => self.json.push_str("\\n"),
+                '\r' => self.json.push_str("\\r"),
+                '\t' => self.json.push_str("\\t"),
+                '\u{8}' => self.json.push_str("\\b"),
+                '\u{c}' => self.json.push_str("\\f"),
+                c if c < ' ' => self.json.push_str(&format!("\\u{:04x}", c as u32)),
+                c => self.json.push(c),
+            }
+        }
+        self.json.push('"');
+    }
+}
@@ -0,0 +1,173 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    environment::EnvRef,
+    impls::{
+        function::{NativeFn, NativeFunction},
+        namespace::Namespace,
+    },
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+pub mod core;
+pub mod fs;
+pub mod json;
+pub mod random;
+pub mod reflection;
+pub mod system;
+pub mod time;
+
+type Result<T> = std::result::Result<T, Exception>;
+
+// Groups of natives an interpreter can be built with, see `Interpreter::with_modules`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Module {
+    // print, clock, type, len
+    Core,
+    // fields, methods, hasField, getField, setField
+    Reflection,
+    // fs.readFile, fs.readLines, fs.writeFile, fs.appendFile, fs.exists, fs.listDir, fs.remove
+    Fs,
+    // args, input, readLine, env, exit
+    System,
+    // json.parse, json.stringify
+    Json,
+    // random, randomInt, choice, shuffle, seed
+    Random,
+    // time.monotonic, time.sleep, time.now, time.date, time.parse...
+    Time,
+}
+
+impl Module {
+    // Safe for untrusted scripts: nothing here reaches files, processes or the environment.
+    // `time.sleep` still blocks the host's thread, so sandboxed hosts must also set a
+    // `Limits::timeout`, sleeping past it stops the script.
+    pub const PURE: &'static [Module] = &[
+        Module::Core,
+        Module::Reflection,
+        Module::Json,
+        Module::Random,
+        Module::Time,
+    ];
+    pub const ALL: &'static [Module] = &[
+        Module::Core,
+        Module::Reflection,
+        Module::Fs,
+        Module::System,
+        Module::Json,
+        Module::Random,
+        Module::Time,
+    ];
+
+    pub fn register(self, globals: &EnvRef) {
+        match self {
+            Module::Core => core::register(globals),
+            Module::Reflection => reflection::register(globals),
+            Module::Fs => fs::register(globals),
+            Module::System => system::register(globals),
+            Module::Json => json::register(globals),
+            Module::Random => random::register(globals),
+            Module::Time => time::register(globals),
+        }
+    }
+}
+
+pub fn define(env: &EnvRef, name: &str, arity: usize, callable: NativeFn) {
+    env.borrow_mut().define(
+        name.into(),
+        Value::NativeFunction(NativeFunction::new(arity, callable)),
+    );
+}
+
+// Natives reached through a namespace instead of being globals, e.g. `json.parse`
+pub fn define_namespace(env: &EnvRef, name: &str, members: &[(&str, usize, NativeFn)]) {
+    let members: HashMap<String, Value> = members
+        .iter()
+        .map(|(member, arity, callable)| {
+            let native = NativeFunction::new(*arity, *callable);
+            (member.to_string(), Value::NativeFunction(native))
+        })
+        .collect();
+
+    env.borrow_mut().define(
+        name.into(),
+        Value::Namespace(Namespace::new(name.into(), members)),
+    );
+}
+
+pub fn string_list(strings: Vec<String>) -> Value {
+    let values = strings.into_iter().map(Value::String).collect();
+    Value::List(Rc::new(RefCell::new(values)))
+}
+
+// Helpers to validate the arguments natives receive
+
+pub fn expect_string(paren: &Token, value: &Value, function: &str) -> Result<String> {
+    match value {
+        Value::String(string) => Ok(string.clone()),
+        _ => Exception::runtime_error(
+            paren.clone(),
+            format!(
+                "{}() expected a string, but got {}.",
+                function,
+                value.type_name()
+            ),
+        ),
+    }
+}
+
+pub fn expect_number(paren: &Token, value: &Value, function: &str) -> Result<f64> {
+    match value {
+        Value::Number(number) => Ok(*number),
+        _ => Exception::runtime_error(
+            paren.clone(),
+            format!(
+                "{}() expected a number, but got {}.",
+                function,
+                value.type_name()
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interpreter::Interpreter,
+        output::Buffer,
+        run,
+        syntax::{token::Literal, token_type::TokenType},
+    };
+
+    fn is_defined(interpreter: &Interpreter, name: &str) -> bool {
+        let token = Token::new(TokenType::Identifier, name.into(), Literal::None, 1);
+        interpreter.globals.borrow().get(&token).is_ok()
+    }
+
+    #[test]
+    fn only_selected_modules_are_registered() {
+        let interpreter = Interpreter::with_modules(&[Module::Core]);
+        assert!(is_defined(&interpreter, "print"));
+        assert!(!is_defined(&interpreter, "fields"));
+
+        let interpreter = Interpreter::with_modules(Module::PURE);
+        assert!(is_defined(&interpreter, "len"));
+        assert!(!is_defined(&interpreter, "fs"));
+
+        let interpreter = Interpreter::with_modules(&[]);
+        assert!(!is_defined(&interpreter, "print"));
+    }
+
+    #[test]
+    fn args_are_empty_outside_run_file() {
+        let buffer = Buffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(buffer.clone());
+
+        run("print(args);".into(), &mut interpreter);
+
+        assert_eq!(buffer.printed(), "[]\n");
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    environment::EnvRef,
+    syntax::{
+        token::Token,
+        value::{ListRef, Value},
+    },
+    Exception,
+};
+
+use super::{define, expect_number};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+// Every native shares the interpreter's generator, so `seed(n)` makes all of them reproducible
+pub fn register(globals: &EnvRef) {
+    define(globals, "random", 0, |interpreter, _, _| {
+        Ok(Value::Number(interpreter.random().next_f64()))
+    });
+
+    // Both ends are included
+    define(globals, "randomInt", 2, |interpreter, paren, args| {
+        let low = expect_integer(paren, &args[0], "randomInt")?;
+        let high = expect_integer(paren, &args[1], "randomInt")?;
+        if low > high {
+            return Exception::runtime_error(
+                paren.clone(),
+                format!(
+                    "randomInt() expected low <= high, but got {} and {}.",
+                    low, high
+                ),
+            );
+        }
+
+        let offset = interpreter.random().below((high - low) as u64 + 1);
+        Ok(Value::Number((low + offset as i64) as f64))
+    });
+
+    define(globals, "choice", 1, |interpreter, paren, args| {
+        let list = expect_list(paren, &args[0], "choice")?;
+        let length = list.borrow().len();
+        if length == 0 {
+            return Exception::runtime_error(
+                paren.clone(),
+                "choice() expected a non-empty list.".into(),
+            );
+        }
+
+        let index = interpreter.random().below(length as u64) as usize;
+        let element = list.borrow()[index].clone();
+        Ok(element)
+    });
+
+    // In place, the list is returned for convenience
+    define(globals, "shuffle", 1, |interpreter, paren, args| {
+        let list = expect_list(paren, &args[0], "shuffle")?;
+        let length = list.borrow().len();
+        for i in (1..length).rev() {
+            let j = interpreter.random().below(i as u64 + 1) as usize;
+            list.borrow_mut().swap(i, j);
+        }
+
+        Ok(Value::List(list))
+    });
+
+    define(globals, "seed", 1, |interpreter, paren, args| {
+        let seed = expect_number(paren, &args[0], "seed")?;
+        interpreter.seed(seed.to_bits());
+        Ok(Value::Nil)
+    });
+}
+
+// Integers beyond 2^53 can't be told apart from their neighbours anyway
+fn expect_integer(paren: &Token, value: &Value, function: &str) -> Result<i64> {
+    let number = expect_number(paren, value, function)?;
+    if number.fract() != 0.0 || number.abs() > 2f64.powi(53) {
+        return Exception::runtime_error(
+            paren.clone(),
+            format!("{}() expected an integer, but got {}.", function, number),
+        );
+    }
+
+    Ok(number as i64)
+}
+
+fn expect_list(paren: &Token, value: &Value, function: &str) -> Result<ListRef> {
+    match value {
+        Value::List(list) => Ok(list.clone()),
+        _ => Exception::runtime_error(
+            paren.clone(),
+            format!(
+                "{}() expected a list, but got {}.",
+                function,
+                value.type_name()
+            ),
+        ),
+    }
+}
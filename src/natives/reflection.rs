@@ -0,0 +1,77 @@
+use crate::{
+    environment::EnvRef,
+    impls::class::ClassInstanceRef,
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+use super::{define, expect_string, string_list};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+pub fn register(globals: &EnvRef) {
+    define(globals, "fields", 1, |_, paren, args| {
+        let instance = expect_instance(paren, &args[0], "fields")?;
+        let names = instance.borrow().field_names();
+        Ok(string_list(names))
+    });
+
+    define(globals, "methods", 1, |_, paren, args| match &args[0] {
+        Value::Class(class) => Ok(string_list(class.method_names())),
+        value => Exception::runtime_error(
+            paren.clone(),
+            format!("methods() expected a class, but got {}.", value.type_name()),
+        ),
+    });
+
+    define(globals, "hasField", 2, |_, paren, args| {
+        let instance = expect_instance(paren, &args[0], "hasField")?;
+        let name = expect_field_name(paren, &args[1], "hasField")?;
+        let has_field = instance.borrow().get_field(&name).is_some();
+        Ok(Value::Boolean(has_field))
+    });
+
+    define(globals, "getField", 2, |_, paren, args| {
+        let instance = expect_instance(paren, &args[0], "getField")?;
+        let name = expect_field_name(paren, &args[1], "getField")?;
+        let field = instance.borrow().get_field(&name);
+        match field {
+            Some(value) => Ok(value),
+            None => Exception::runtime_error(paren.clone(), format!("Undefined field '{}'.", name)),
+        }
+    });
+
+    define(globals, "setField", 3, |_, paren, args| {
+        let instance = expect_instance(paren, &args[0], "setField")?;
+        let name = expect_field_name(paren, &args[1], "setField")?;
+        instance.borrow_mut().set_field(name, args[2].clone());
+        Ok(args[2].clone())
+    });
+}
+
+fn expect_instance(paren: &Token, value: &Value, function: &str) -> Result<ClassInstanceRef> {
+    match value {
+        Value::ClassInstance(instance) => Ok(instance.clone()),
+        _ => Exception::runtime_error(
+            paren.clone(),
+            format!(
+                "{}() expected an instance, but got {}.",
+                function,
+                value.type_name()
+            ),
+        ),
+    }
+}
+
+// Reflection must not be a way around private members
+fn expect_field_name(paren: &Token, value: &Value, function: &str) -> Result<String> {
+    let name = expect_string(paren, value, function)?;
+    if name.starts_with('#') {
+        return Exception::runtime_error(
+            paren.clone(),
+            format!("Can't access private member '{}'.", name),
+        );
+    }
+
+    Ok(name)
+}
@@ -0,0 +1,66 @@
+use std::{env, io};
+
+use crate::{
+    environment::EnvRef,
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+use super::{define, expect_number, expect_string, string_list};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+pub fn register(globals: &EnvRef) {
+    // Command line arguments, `run_file` replaces them with the ones after the script's path
+    globals
+        .borrow_mut()
+        .define("args".into(), string_list(vec![]));
+
+    define(globals, "input", 1, |interpreter, paren, args| {
+        let prompt = interpreter.stringfy(&args[0], paren)?;
+        interpreter.prompt(&prompt);
+        read_line(paren)
+    });
+
+    define(globals, "readLine", 0, |_, paren, _| read_line(paren));
+
+    define(globals, "env", 1, |_, paren, args| {
+        let name = expect_string(paren, &args[0], "env")?;
+        match env::var(name) {
+            Ok(value) => Ok(Value::String(value)),
+            Err(_) => Ok(Value::Nil),
+        }
+    });
+
+    define(globals, "exit", 1, |_, paren, args| {
+        let code = expect_number(paren, &args[0], "exit")?;
+        if code.fract() != 0.0 || !(0.0..=255.0).contains(&code) {
+            return Exception::runtime_error(
+                paren.clone(),
+                format!(
+                    "Exit code must be an integer between 0 and 255, but got {}.",
+                    code
+                ),
+            );
+        }
+
+        Err(Exception::Exit(code as i32))
+    });
+}
+
+// Without the line break, nil once stdin is closed
+fn read_line(paren: &Token) -> Result<Value> {
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => Ok(Value::Nil),
+        Ok(_) => {
+            let length = line.trim_end_matches(['\n', '\r']).len();
+            line.truncate(length);
+            Ok(Value::String(line))
+        }
+        Err(error) => Exception::runtime_error(
+            paren.clone(),
+            format!("Couldn't read from stdin: {}.", error),
+        ),
+    }
+}
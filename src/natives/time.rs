@@ -0,0 +1,128 @@
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    environment::EnvRef,
+    impls::{date::Date, function::NativeFn},
+    interpreter::Interpreter,
+    syntax::{token::Token, value::Value},
+    Exception,
+};
+
+use super::{define_namespace, expect_number, expect_string};
+
+type Result<T> = std::result::Result<T, Exception>;
+
+// Where `monotonic()` counts from
+static START: OnceLock<Instant> = OnceLock::new();
+
+pub fn register(globals: &EnvRef) {
+    START.get_or_init(Instant::now);
+
+    define_namespace(
+        globals,
+        "time",
+        &[
+            ("monotonic", 0, monotonic as NativeFn),
+            ("sleep", 1, sleep),
+            ("now", 0, now),
+            ("fromTimestamp", 1, from_timestamp),
+            ("date", 1, date),
+            ("parse", 1, parse),
+            ("seconds", 1, |_, paren, args| {
+                duration(paren, &args[0], "seconds", 1000.0)
+            }),
+            ("minutes", 1, |_, paren, args| {
+                duration(paren, &args[0], "minutes", 60_000.0)
+            }),
+            ("hours", 1, |_, paren, args| {
+                duration(paren, &args[0], "hours", 3_600_000.0)
+            }),
+            ("days", 1, |_, paren, args| {
+                duration(paren, &args[0], "days", 86_400_000.0)
+            }),
+        ],
+    );
+}
+
+// Milliseconds that never go backwards, for measuring how long things take
+fn monotonic(_: &mut Interpreter, _: &Token, _: Vec<Value>) -> Result<Value> {
+    let elapsed = START.get_or_init(Instant::now).elapsed();
+    Ok(Value::Number(elapsed.as_secs_f64() * 1000.0))
+}
+
+fn sleep(interpreter: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let millis = expect_number(paren, &args[0], "sleep")?;
+    if !(millis >= 0.0 && millis.is_finite()) {
+        return Exception::runtime_error(
+            paren.clone(),
+            format!("sleep() expected a positive duration, but got {}.", millis),
+        );
+    }
+
+    match Duration::try_from_secs_f64(millis / 1000.0) {
+        Ok(duration) => interpreter.sleep(duration)?,
+        Err(_) => {
+            return Exception::runtime_error(
+                paren.clone(),
+                format!("sleep() duration is too long, got {}.", millis),
+            )
+        }
+    }
+    Ok(Value::Nil)
+}
+
+fn now(_: &mut Interpreter, paren: &Token, _: Vec<Value>) -> Result<Value> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    date_value(paren, Date::from_timestamp(timestamp.as_millis() as f64))
+}
+
+fn from_timestamp(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let millis = expect_number(paren, &args[0], "fromTimestamp")?;
+    date_value(paren, Date::from_timestamp(millis))
+}
+
+// `time.date([2024, 3, 1])` or with the time too, `time.date([2024, 3, 1, 12, 30, 0, 0])`
+fn date(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let components = match &args[0] {
+        Value::List(list) => list.borrow().clone(),
+        value => {
+            return Exception::runtime_error(
+                paren.clone(),
+                format!(
+                    "date() expected a list of components, but got {}.",
+                    value.type_name()
+                ),
+            )
+        }
+    };
+
+    let mut numbers = vec![];
+    for component in &components {
+        numbers.push(expect_number(paren, component, "date")?);
+    }
+    date_value(paren, Date::from_components(&numbers))
+}
+
+fn parse(_: &mut Interpreter, paren: &Token, args: Vec<Value>) -> Result<Value> {
+    let text = expect_string(paren, &args[0], "parse")?;
+    date_value(paren, Date::parse(&text))
+}
+
+// Durations are numbers of milliseconds, so they can be added and compared like any number
+fn duration(paren: &Token, value: &Value, function: &str, millis: f64) -> Result<Value> {
+    Ok(Value::Number(
+        expect_number(paren, value, function)? * millis,
+    ))
+}
+
+fn date_value(paren: &Token, date: std::result::Result<Date, String>) -> Result<Value> {
+    match date {
+        Ok(date) => Ok(Value::Date(date)),
+        Err(message) => {
+            Exception::runtime_error(paren.clone(), format!("Invalid date: {}.", message))
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+pub type OutputRef = Rc<RefCell<dyn Output>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    // Lines written by `print`
+    Print,
+    // Text `input` shows before reading a line, it doesn't end with a line break
+    Prompt,
+    // Compile errors, runtime errors and warnings
+    Error,
+}
+
+// Where everything a script writes ends up, see `Interpreter::set_output`.
+// Closures are outputs too, which is handy to forward lines to a logger.
+pub trait Output {
+    fn write(&mut self, stream: Stream, line: &str);
+}
+
+impl<F: FnMut(Stream, &str)> Output for F {
+    fn write(&mut self, stream: Stream, line: &str) {
+        self(stream, line)
+    }
+}
+
+// The default: printed lines go to stdout and errors to stderr
+pub struct Stdout;
+
+impl Output for Stdout {
+    fn write(&mut self, stream: Stream, line: &str) {
+        match stream {
+            Stream::Print => println!("{}", line),
+            Stream::Prompt => {
+                print!("{}", line);
+                let _ = io::stdout().flush();
+            }
+            Stream::Error => eprintln!("{}", line),
+        }
+    }
+}
+
+// Keeps everything in memory. Clones share the same contents, so keep one
+// to read what the script wrote after handing the other to the interpreter.
+// Prompts are kept with the printed lines.
+#[derive(Debug, Clone, Default)]
+pub struct Buffer {
+    printed: Rc<RefCell<String>>,
+    errors: Rc<RefCell<String>>,
+}
+
+impl Buffer {
+    pub fn new() -> Buffer {
+        Buffer::default()
+    }
+
+    pub fn printed(&self) -> String {
+        self.printed.borrow().clone()
+    }
+
+    pub fn errors(&self) -> String {
+        self.errors.borrow().clone()
+    }
+}
+
+impl Output for Buffer {
+    fn write(&mut self, stream: Stream, line: &str) {
+        let mut contents = match stream {
+            Stream::Print | Stream::Prompt => self.printed.borrow_mut(),
+            Stream::Error => self.errors.borrow_mut(),
+        };
+        contents.push_str(line);
+        if stream != Stream::Prompt {
+            contents.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interpreter::Interpreter, run};
+
+    #[test]
+    fn buffer_captures_prints_and_errors() {
+        let buffer = Buffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(buffer.clone());
+
+        run(
+            "print(1 + 2); print(\"lox\"); 1 + nil;".into(),
+            &mut interpreter,
+        );
+
+        assert_eq!(buffer.printed(), "3\nlox\n");
+        assert_eq!(
+            buffer.errors(),
+            "Error at line 1: Operands must be numbers.\n"
+        );
+    }
+
+    #[test]
+    fn prompts_stay_on_the_line() {
+        let buffer = Buffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(buffer.clone());
+
+        interpreter.prompt("Name: ");
+        interpreter.print("lox");
+
+        assert_eq!(buffer.printed(), "Name: lox\n");
+    }
+
+    #[test]
+    fn closures_receive_every_line() {
+        let lines = Rc::new(RefCell::new(vec![]));
+        let log = Rc::clone(&lines);
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(move |stream, line: &str| {
+            log.borrow_mut().push((stream, line.to_string()))
+        });
+
+        run("print(\"hi\");".into(), &mut interpreter);
+
+        assert_eq!(*lines.borrow(), vec![(Stream::Print, "hi".to_string())]);
+    }
+}
@@ -2,7 +2,8 @@ use crate::{
     print_error,
     syntax::{
         expr::Expr,
-        stmt::Stmt,
+        pattern::{MatchCase, Pattern, Target},
+        stmt::{ClassDeclaration, Stmt},
         token::{Literal, Token},
         token_type::TokenType,
     },
@@ -39,10 +40,16 @@ impl Parser {
     fn declaration(&mut self) -> Option<Stmt> {
         let result = if self.match_token(&[TokenType::Var]) {
             self.var_declaration()
+        } else if self.match_token(&[TokenType::Const]) {
+            self.const_declaration()
         } else if self.match_token(&[TokenType::Fun]) {
             self.function("function".into())
         } else if self.match_token(&[TokenType::Class]) {
             self.class_declaration()
+        } else if self.match_token(&[TokenType::Trait]) {
+            self.trait_declaration()
+        } else if self.match_token(&[TokenType::Enum]) {
+            self.enum_declaration()
         } else {
             self.statement()
         };
@@ -65,35 +72,105 @@ impl Parser {
             super_class = Some(Expr::Variable { uid: new_uid(), name });
         }
 
+        let mut traits = vec![];
+        if self.match_token(&[TokenType::With]) {
+            loop {
+                let name = self.consume(TokenType::Identifier, "Expected trait name.")?;
+                traits.push(Expr::Variable {
+                    uid: new_uid(),
+                    name,
+                });
+
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
         self.consume(TokenType::LeftBrace, "Expected '{' before class body.")?;
 
+        let mut abstract_methods = vec![];
         let mut getters = vec![];
         let mut methods = vec![];
+        let mut private_methods = vec![];
+        let mut setters = vec![];
         let mut static_methods = vec![];
 
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
             if self.match_token(&[TokenType::Class]) {
                 // Static methods
                 static_methods.push(self.function("static method".into())?);
+            } else if self.match_token(&[TokenType::PrivateIdentifier]) {
+                // Private methods
+                let name = self.previous();
+                private_methods.push(self.function_body(name, "private method".into())?);
+            } else if self.peek().lexeme == "set"
+                && self.peek_next().token_type == TokenType::Identifier
+            {
+                // `set` is only contextual: `set(...)` is still a regular method
+                self.advance();
+                setters.push(self.setter()?);
             } else if !self.is_at_end() && self.peek_next().token_type == TokenType::LeftBrace {
                 getters.push(self.getter()?);
             } else {
                 // Instance methods
-                methods.push(self.function("method".into())?);
+                self.method(&mut methods, &mut abstract_methods)?;
             }
         }
 
         self.consume(TokenType::RightBrace, "Expected '}' after class body")?;
 
-        Ok(Stmt::Class {
+        Ok(Stmt::Class(ClassDeclaration {
+            abstract_methods,
             getters,
             name,
             methods,
+            private_methods,
+            setters,
             static_methods,
             super_class,
+            traits,
+        }))
+    }
+
+    fn trait_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expected a trait name.")?;
+        self.consume(TokenType::LeftBrace, "Expected '{' before trait body.")?;
+
+        let mut abstract_methods = vec![];
+        let mut methods = vec![];
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            self.method(&mut methods, &mut abstract_methods)?;
+        }
+
+        self.consume(TokenType::RightBrace, "Expected '}' after trait body")?;
+
+        Ok(Stmt::Trait {
+            abstract_methods,
+            name,
+            methods,
         })
     }
 
+    fn enum_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expected an enum name.")?;
+        self.consume(TokenType::LeftBrace, "Expected '{' before enum body.")?;
+
+        // A trailing comma after the last variant is allowed
+        let mut variants = vec![];
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            variants.push(self.consume(TokenType::Identifier, "Expected a variant name.")?);
+
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expected '}' after enum variants.")?;
+
+        Ok(Stmt::Enum { name, variants })
+    }
+
     fn getter(&mut self) -> Result<Stmt> {
         let name = self.consume(TokenType::Identifier, "Expected getter name.")?;
         self.consume(TokenType::LeftBrace, "Expected '{' after getter name.")?;
@@ -108,6 +185,21 @@ impl Parser {
         })
     }
 
+    fn setter(&mut self) -> Result<Stmt> {
+        let setter = self.function("setter".into())?;
+
+        if let Stmt::Function {
+            name, parameters, ..
+        } = &setter
+        {
+            if parameters.len() != 1 {
+                return Err(self.error(name.clone(), "Setters must take exactly one parameter."));
+            }
+        }
+
+        Ok(setter)
+    }
+
     fn statement(&mut self) -> Result<Stmt> {
         if self.match_token(&[TokenType::If]) {
             return self.if_statement();
@@ -118,9 +210,16 @@ impl Parser {
         if self.match_token(&[TokenType::Return]) {
             return self.return_stmt();
         }
+        if self.match_token(&[TokenType::Yield]) {
+            return self.yield_stmt();
+        }
         if self.match_token(&[TokenType::For]) {
             return self.for_stmt();
         }
+        if self.is_match_stmt() {
+            self.advance();
+            return self.match_stmt();
+        }
         if self.match_token(&[TokenType::LeftBrace]) {
             return Ok(Stmt::Block {
                 statements: self.block().unwrap_or(vec![]),
@@ -130,6 +229,156 @@ impl Parser {
         self.expression_stmt()
     }
 
+    // `match` and `case` are only contextual, so they can still be used as names.
+    // `match (subject) {` starts a match statement, `match(subject);` is a call.
+    fn is_match_stmt(&self) -> bool {
+        if self.peek().token_type != TokenType::Identifier
+            || self.peek().lexeme != "match"
+            || !self.check_ahead(1, &TokenType::LeftParen)
+        {
+            return false;
+        }
+
+        let mut depth = 0;
+        for distance in 1.. {
+            match self.tokens.get(self.current + distance) {
+                Some(token) if token.token_type == TokenType::LeftParen => depth += 1,
+                Some(token) if token.token_type == TokenType::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.check_ahead(distance + 1, &TokenType::LeftBrace);
+                    }
+                }
+                Some(token) if token.token_type != TokenType::Eof => (),
+                _ => return false,
+            }
+        }
+
+        false
+    }
+
+    fn match_stmt(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        self.consume(TokenType::LeftParen, "Expected '(' after match keyword.")?;
+        let subject = self.expression()?;
+        self.consume(TokenType::RightParen, "Expected ')' after match subject.")?;
+        self.consume(TokenType::LeftBrace, "Expected '{' before match cases.")?;
+
+        let mut cases = vec![];
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            if !(self.check(&TokenType::Identifier) && self.peek().lexeme == "case") {
+                return Err(self.error(self.peek(), "Expected 'case' in match body."));
+            }
+            let case_keyword = self.advance();
+
+            let mut patterns = vec![self.pattern()?];
+            while self.match_token(&[TokenType::Comma]) {
+                patterns.push(self.pattern()?);
+            }
+
+            let guard = if self.match_token(&[TokenType::If]) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+
+            self.consume(TokenType::FatArrow, "Expected '=>' after case pattern.")?;
+            let body = self.statement()?;
+
+            cases.push(MatchCase {
+                keyword: case_keyword,
+                patterns,
+                guard,
+                body: Box::new(body),
+            });
+        }
+
+        self.consume(TokenType::RightBrace, "Expected '}' after match cases.")?;
+
+        Ok(Stmt::Match {
+            keyword,
+            subject,
+            cases,
+        })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern> {
+        if self.match_token(&[TokenType::Identifier]) {
+            let name = self.previous();
+
+            if name.lexeme == "_" {
+                return Ok(Pattern::Wildcard);
+            }
+
+            let mut expr = Expr::Variable {
+                uid: new_uid(),
+                name,
+            };
+
+            if self.match_token(&[TokenType::LeftParen]) {
+                let mut fields = vec![];
+                if !self.check(&TokenType::RightParen) {
+                    loop {
+                        fields.push(self.pattern()?);
+                        if !self.match_token(&[TokenType::Comma]) {
+                            break;
+                        }
+                    }
+                }
+                let paren = self.consume(TokenType::RightParen, "Expected ')' after fields.")?;
+
+                return Ok(Pattern::Instance {
+                    class: expr,
+                    paren,
+                    fields,
+                });
+            }
+
+            if !self.check(&TokenType::Dot) {
+                if let Expr::Variable { name, .. } = expr {
+                    return Ok(Pattern::Binding(name));
+                }
+            }
+
+            // Constants such as `Color.Red`
+            while self.match_token(&[TokenType::Dot]) {
+                let name =
+                    self.consume(TokenType::Identifier, "Expected property name after '.'.")?;
+                expr = Expr::Get {
+                    uid: new_uid(),
+                    name,
+                    object: Box::new(expr),
+                };
+            }
+
+            return Ok(Pattern::Value(expr));
+        }
+
+        if self.match_token(&[TokenType::Minus]) {
+            let operator = self.previous();
+            let number = self.consume(TokenType::Number, "Expected a number after '-'.")?;
+            return Ok(Pattern::Value(Expr::Unary {
+                uid: new_uid(),
+                operator,
+                right: Box::new(Expr::Literal {
+                    uid: new_uid(),
+                    value: number.literal,
+                }),
+            }));
+        }
+
+        if self.check(&TokenType::Number)
+            || self.check(&TokenType::String)
+            || self.check(&TokenType::True)
+            || self.check(&TokenType::False)
+            || self.check(&TokenType::Nil)
+        {
+            return Ok(Pattern::Value(self.primary()?));
+        }
+
+        Err(self.error(self.peek(), "Expected a pattern."))
+    }
+
     fn if_statement(&mut self) -> Result<Stmt> {
         self.consume(TokenType::LeftParen, "Expected '(' after if keyword.")?;
         let condition = self.expression()?;
@@ -159,7 +408,32 @@ impl Parser {
         Ok(Stmt::Return { keyword, value })
     }
 
+    fn yield_stmt(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expected ';' after yield value.")?;
+
+        Ok(Stmt::Yield { keyword, value })
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt> {
+        if self.check(&TokenType::LeftBracket) || self.check(&TokenType::LeftBrace) {
+            let target = self.target()?;
+            self.consume(
+                TokenType::Equal,
+                "Expected '=' after destructuring pattern.",
+            )?;
+            let initializer = self.expression()?;
+            self.consume(
+                TokenType::Semicolon,
+                "Expected ';' after variable declaration.",
+            )?;
+            return Ok(Stmt::Destructure {
+                target,
+                initializer,
+            });
+        }
+
         let name = self.consume(TokenType::Identifier, "Expected a variable name.")?;
 
         let mut initializer = None;
@@ -174,6 +448,99 @@ impl Parser {
         Ok(Stmt::Var { name, initializer })
     }
 
+    fn const_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expected a constant name.")?;
+        self.consume(TokenType::Equal, "Constants must be initialized.")?;
+        let initializer = self.expression()?;
+        self.consume(
+            TokenType::Semicolon,
+            "Expected ';' after constant declaration.",
+        )?;
+
+        Ok(Stmt::Const { name, initializer })
+    }
+
+    fn target(&mut self) -> Result<Target> {
+        if self.match_token(&[TokenType::LeftBracket]) {
+            let bracket = self.previous();
+            let mut elements = vec![];
+            let mut rest = None;
+
+            if !self.check(&TokenType::RightBracket) {
+                loop {
+                    // The rest element must be the last one
+                    if self.match_token(&[TokenType::DotDotDot]) {
+                        rest = Some(self.consume(
+                            TokenType::Identifier,
+                            "Expected a variable name after '...'.",
+                        )?);
+                        break;
+                    }
+
+                    elements.push(self.target()?);
+                    if !self.match_token(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+            }
+
+            self.consume(
+                TokenType::RightBracket,
+                "Expected ']' after destructuring pattern.",
+            )?;
+            return Ok(Target::List {
+                bracket,
+                elements,
+                rest,
+            });
+        }
+
+        if self.match_token(&[TokenType::LeftBrace]) {
+            let brace = self.previous();
+            let mut names = vec![];
+
+            if !self.check(&TokenType::RightBrace) {
+                loop {
+                    names.push(self.consume(TokenType::Identifier, "Expected a field name.")?);
+                    if !self.match_token(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+            }
+
+            self.consume(
+                TokenType::RightBrace,
+                "Expected '}' after destructuring pattern.",
+            )?;
+            return Ok(Target::Object { brace, names });
+        }
+
+        let name = self.consume(TokenType::Identifier, "Expected a variable name.")?;
+        Ok(Target::Name(name))
+    }
+
+    // Destructured parameters and loop variables are bound to a hidden variable,
+    // which is destructured by the first statement of the body
+    fn destructure_hidden(&self, target: Target, index: usize) -> (Token, Stmt) {
+        let line = self.previous().line;
+        let hidden = Token::new(
+            TokenType::Identifier,
+            format!("#destructured{}", index),
+            Literal::None,
+            line,
+        );
+
+        let stmt = Stmt::Destructure {
+            target,
+            initializer: Expr::Variable {
+                uid: new_uid(),
+                name: hidden.clone(),
+            },
+        };
+
+        (hidden, stmt)
+    }
+
     fn while_stmt(&mut self) -> Result<Stmt> {
         self.consume(TokenType::LeftParen, "Expected '(' after 'while'.")?;
         let condition = self.expression()?;
@@ -187,6 +554,13 @@ impl Parser {
     fn for_stmt(&mut self) -> Result<Stmt> {
         self.consume(TokenType::LeftParen, "Expected '(' after for statement.")?;
 
+        let is_destructuring = self.check_ahead(1, &TokenType::LeftBracket)
+            || self.check_ahead(1, &TokenType::LeftBrace);
+        if self.check(&TokenType::Var) && (is_destructuring || self.check_ahead(2, &TokenType::In))
+        {
+            return self.for_in_stmt();
+        }
+
         let initializer;
         if self.match_token(&[TokenType::Semicolon]) {
             initializer = None;
@@ -236,6 +610,35 @@ impl Parser {
         Ok(body)
     }
 
+    // `for (var x in iterable)` isn't desugared, the interpreter walks the iterable
+    fn for_in_stmt(&mut self) -> Result<Stmt> {
+        self.consume(TokenType::Var, "Expected 'var' in for-in loop.")?;
+        let target = self.target()?;
+        let keyword = self.consume(TokenType::In, "Expected 'in' after loop variable.")?;
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expected ')' after for-in clauses.")?;
+
+        let mut body = self.statement()?;
+
+        let name = match target {
+            Target::Name(name) => name,
+            target => {
+                let (hidden, destructure) = self.destructure_hidden(target, 0);
+                body = Stmt::Block {
+                    statements: vec![destructure, body],
+                };
+                hidden
+            }
+        };
+
+        Ok(Stmt::ForIn {
+            name,
+            keyword,
+            iterable,
+            body: Box::new(body),
+        })
+    }
+
     fn expression_stmt(&mut self) -> Result<Stmt> {
         let expr = self.expression()?;
         self.consume(TokenType::Semicolon, "Expected ';' after value.")?;
@@ -248,18 +651,52 @@ impl Parser {
             format!("Expected {} name.", kind).as_str(),
         )?;
 
+        self.function_body(name, kind)
+    }
+
+    fn function_body(&mut self, name: Token, kind: String) -> Result<Stmt> {
+        let parameters = self.parameters(&kind)?;
+        self.function_block(name, parameters, kind)
+    }
+
+    // Methods declared without a body are abstract: subclasses must implement them
+    fn method(&mut self, methods: &mut Vec<Stmt>, abstract_methods: &mut Vec<Token>) -> Result<()> {
+        let name = self.consume(TokenType::Identifier, "Expected method name.")?;
+        let parameters = self.parameters("method")?;
+
+        if self.match_token(&[TokenType::Semicolon]) {
+            abstract_methods.push(name);
+        } else {
+            methods.push(self.function_block(name, parameters, "method".into())?);
+        }
+
+        Ok(())
+    }
+
+    // Destructured parameters also return the statements binding them
+    fn parameters(&mut self, kind: &str) -> Result<(Vec<Token>, Vec<Stmt>)> {
         self.consume(
             TokenType::LeftParen,
             format!("Expected '(' after {} name.", kind).as_str(),
         )?;
         let mut parameters = vec![];
+        let mut destructures = vec![];
 
         if !self.check(&TokenType::RightParen) {
             loop {
                 if parameters.len() >= 255 {
                     self.error(self.peek().clone(), "Can't have more than 255 parameters");
                 }
-                parameters.push(self.consume(TokenType::Identifier, "Expected a parameter name.")?);
+
+                if self.check(&TokenType::LeftBracket) || self.check(&TokenType::LeftBrace) {
+                    let target = self.target()?;
+                    let (hidden, destructure) = self.destructure_hidden(target, parameters.len());
+                    parameters.push(hidden);
+                    destructures.push(destructure);
+                } else {
+                    parameters
+                        .push(self.consume(TokenType::Identifier, "Expected a parameter name.")?);
+                }
 
                 if !self.match_token(&[TokenType::Comma]) {
                     break;
@@ -272,12 +709,21 @@ impl Parser {
             format!("Expected ')' after {} params list.", kind).as_str(),
         )?;
 
+        Ok((parameters, destructures))
+    }
+
+    fn function_block(
+        &mut self,
+        name: Token,
+        (parameters, mut body): (Vec<Token>, Vec<Stmt>),
+        kind: String,
+    ) -> Result<Stmt> {
         self.consume(
             TokenType::LeftBrace,
             format!("Expected '{{' before {} body.", kind).as_str(),
         )?;
 
-        let body = self.block()?;
+        body.extend(self.block()?);
 
         Ok(Stmt::Function {
             name,
@@ -323,6 +769,20 @@ impl Parser {
                     object,
                     value: Box::new(value),
                 });
+            } else if let Expr::Index {
+                object,
+                bracket,
+                index,
+                ..
+            } = expr
+            {
+                return Ok(Expr::SetIndex {
+                    uid: new_uid(),
+                    object,
+                    bracket,
+                    index,
+                    value: Box::new(value),
+                });
             }
 
             return Err(self.error(equals, "Invalid assignment target."));
@@ -383,15 +843,16 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expr> {
-        let mut expr = self.term();
+        let mut expr = self.range();
         while self.match_token(&[
             TokenType::Greater,
             TokenType::GreaterEqual,
             TokenType::Less,
             TokenType::LessEqual,
+            TokenType::Is,
         ]) {
             let operator = self.previous();
-            let right = self.term();
+            let right = self.range();
             expr = Ok(Expr::Binary {
                 uid: new_uid(),
                 left: Box::new(expr?),
@@ -402,6 +863,22 @@ impl Parser {
         expr
     }
 
+    // Ranges don't chain, `0..1..2` is a syntax error
+    fn range(&mut self) -> Result<Expr> {
+        let expr = self.term()?;
+        if self.match_token(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+            let operator = self.previous();
+            let right = self.term()?;
+            return Ok(Expr::Binary {
+                uid: new_uid(),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
     fn term(&mut self) -> Result<Expr> {
         let mut expr = self.factor();
         while self.match_token(&[TokenType::Plus, TokenType::Minus]) {
@@ -473,6 +950,53 @@ impl Parser {
         })
     }
 
+    fn list(&mut self) -> Result<Expr> {
+        let mut elements = vec![];
+
+        if !self.check(&TokenType::RightBracket) {
+            loop {
+                elements.push(self.expression()?);
+
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightBracket, "Expected ']' after list elements.")?;
+
+        Ok(Expr::List {
+            uid: new_uid(),
+            elements,
+        })
+    }
+
+    fn map(&mut self) -> Result<Expr> {
+        let brace = self.previous();
+        let mut entries = vec![];
+
+        if !self.check(&TokenType::RightBrace) {
+            loop {
+                let key = self.expression()?;
+                self.consume(TokenType::Colon, "Expected ':' after map key.")?;
+                let value = self.expression()?;
+                entries.push((key, value));
+
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expected '}' after map entries.")?;
+
+        Ok(Expr::Map {
+            uid: new_uid(),
+            brace,
+            entries,
+        })
+    }
+
     fn call(&mut self) -> Result<Expr> {
         let mut expr = self.primary()?;
 
@@ -480,13 +1004,27 @@ impl Parser {
             if self.match_token(&[TokenType::LeftParen]) {
                 expr = self.finish_call(expr)?;
             } else if self.match_token(&[TokenType::Dot]) {
-                let name =
-                    self.consume(TokenType::Identifier, "Expected property name after '.'.")?;
+                // `instance.class` is the only keyword allowed as a property name,
+                // private members (`this.#name`) are checked by the resolver
+                let name = if self.match_token(&[TokenType::Class, TokenType::PrivateIdentifier]) {
+                    self.previous()
+                } else {
+                    self.consume(TokenType::Identifier, "Expected property name after '.'.")?
+                };
                 expr = Expr::Get {
                     uid: new_uid(),
                     name,
                     object: Box::new(expr),
                 };
+            } else if self.match_token(&[TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, "Expected ']' after index.")?;
+                expr = Expr::Index {
+                    uid: new_uid(),
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                };
             } else {
                 break;
             }
@@ -544,6 +1082,14 @@ impl Parser {
             });
         }
 
+        if self.match_token(&[TokenType::LeftBracket]) {
+            return self.list();
+        }
+
+        if self.match_token(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+
         if self.match_token(&[TokenType::LeftParen]) {
             let expr = self.expression();
             self.consume(TokenType::RightParen, "Expected ')' after expression.")?;
@@ -574,6 +1120,14 @@ impl Parser {
         Err(self.error(self.peek(), msg))
     }
 
+    // Looks `distance` tokens past the current one
+    fn check_ahead(&self, distance: usize, token_type: &TokenType) -> bool {
+        match self.tokens.get(self.current + distance) {
+            Some(token) => token.token_type == *token_type,
+            None => false,
+        }
+    }
+
     fn check(&self, token_type: &TokenType) -> bool {
         if self.is_at_end() {
             return false;
@@ -620,13 +1174,17 @@ impl Parser {
 
             match self.peek().token_type {
                 TokenType::Class
+                | TokenType::Trait
+                | TokenType::Enum
                 | TokenType::Fun
                 | TokenType::Var
+                | TokenType::Const
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => return,
+                | TokenType::Return
+                | TokenType::Yield => return,
                 _ => {
                     self.advance();
                 }
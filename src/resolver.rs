@@ -5,10 +5,12 @@ use crate::{
     print_error,
     syntax::{
         expr::{self, Expr, Visitor},
-        stmt::{self, Stmt},
+        pattern::{MatchCase, Pattern, Target},
+        stmt::{self, ClassDeclaration, Stmt},
         token::Token,
+        token_type::TokenType,
     },
-    RuntimeError,
+    warning, RuntimeError,
 };
 
 #[derive(Clone, Copy)]
@@ -17,6 +19,7 @@ enum FunctionType {
     None,
     Method,
     Initializer,
+    Generator,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -24,11 +27,13 @@ enum ClassType {
     None,
     Subclass,
     Class,
+    Trait,
 }
 
 struct State {
     pub is_ready: bool,
     pub is_used: bool,
+    pub is_const: bool,
     pub token: Token,
 }
 
@@ -37,16 +42,27 @@ impl State {
         State {
             is_ready,
             is_used,
+            is_const: false,
             token,
         }
     }
 }
 
+// What the resolver remembers about classes and traits declared so far,
+// used to check that subclasses implement the abstract methods they inherit
+#[derive(Default)]
+struct Declaration {
+    abstract_methods: Vec<String>,
+    methods: Vec<String>,
+    super_class: Option<String>,
+}
+
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
     scopes: Vec<HashMap<String, State>>,
     current_function: FunctionType,
     current_class: ClassType,
+    declarations: HashMap<String, Declaration>,
 }
 
 impl Resolver<'_> {
@@ -56,6 +72,7 @@ impl Resolver<'_> {
             scopes: vec![],
             current_function: FunctionType::None,
             current_class: ClassType::None,
+            declarations: HashMap::new(),
         }
     }
 
@@ -128,7 +145,12 @@ impl Resolver<'_> {
         _type_: FunctionType,
     ) {
         let enclosing = self.current_function;
-        self.current_function = _type_;
+        self.current_function = match _type_ {
+            FunctionType::Function | FunctionType::Method if Stmt::yields(body) => {
+                FunctionType::Generator
+            }
+            _ => _type_,
+        };
 
         self.begin_scope();
         for param in parameters {
@@ -143,7 +165,9 @@ impl Resolver<'_> {
 
     fn resolve_local(&mut self, expr: &Expr, name: &Token) {
         for i in (0..self.scopes.len()).rev() {
-            if self.scopes[i].contains_key(&name.lexeme) {
+            // Reads from nested scopes (loop bodies, closures...) count as well
+            if let Some(state) = self.scopes[i].get_mut(&name.lexeme) {
+                state.is_used = true;
                 let hoops_away = self.scopes.len() - 1 - i;
                 self.interpreter.resolve(expr, hoops_away);
                 return;
@@ -193,14 +217,86 @@ impl Resolver<'_> {
         self.end_scope();
     }
 
-    fn visit_class_stmt(
-        &mut self,
-        getters: &Vec<Stmt>,
-        name: &Token,
-        methods: &Vec<Stmt>,
-        static_methods: &Vec<Stmt>,
-        super_class: &Option<Expr>,
-    ) {
+    fn method_names(methods: &[Stmt]) -> Vec<String> {
+        methods
+            .iter()
+            .filter_map(|method| match method {
+                Stmt::Function { name, .. } => Some(name.lexeme.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn is_implemented(&self, class_name: &String, method: &String) -> bool {
+        match self.declarations.get(class_name) {
+            Some(declaration) => {
+                declaration.methods.contains(method)
+                    || declaration
+                        .super_class
+                        .as_ref()
+                        .is_some_and(|super_class| self.is_implemented(super_class, method))
+            }
+            None => false,
+        }
+    }
+
+    fn collect_abstract_methods(&self, class_name: &String, into: &mut Vec<String>) {
+        if let Some(declaration) = self.declarations.get(class_name) {
+            into.extend(declaration.abstract_methods.iter().cloned());
+            if let Some(super_class) = &declaration.super_class {
+                self.collect_abstract_methods(super_class, into);
+            }
+        }
+    }
+
+    // A subclass that doesn't declare abstract methods itself is meant to be concrete,
+    // so every abstract method it inherits should be implemented somewhere.
+    fn check_abstract_methods(&mut self, name: &Token, abstract_methods: &[Token]) {
+        if !abstract_methods.is_empty() {
+            return;
+        }
+
+        let mut inherited = vec![];
+        self.collect_abstract_methods(&name.lexeme, &mut inherited);
+
+        for method in inherited {
+            if !self.is_implemented(&name.lexeme, &method) {
+                warning(
+                    name,
+                    &format!(
+                        "Class '{}' doesn't implement abstract method '{}'.",
+                        name.lexeme, method
+                    ),
+                );
+            }
+        }
+    }
+
+    fn declaration_of(
+        abstract_methods: &[Token],
+        methods: &[Stmt],
+        super_class: Option<String>,
+    ) -> Declaration {
+        Declaration {
+            abstract_methods: abstract_methods.iter().map(|t| t.lexeme.clone()).collect(),
+            methods: Resolver::method_names(methods),
+            super_class,
+        }
+    }
+
+    fn visit_class_stmt(&mut self, declaration: &ClassDeclaration) {
+        let ClassDeclaration {
+            abstract_methods,
+            getters,
+            name,
+            methods,
+            private_methods,
+            setters,
+            static_methods,
+            super_class,
+            traits,
+        } = declaration;
+
         let enclosing_class = self.current_class;
         self.current_class = ClassType::Class;
 
@@ -208,8 +304,45 @@ impl Resolver<'_> {
         self.define(name);
 
         if let Some(super_class) = super_class {
-            self.current_class = ClassType::Subclass;
             self.resolve_super_class(name, super_class);
+        }
+
+        for mixin in traits {
+            self.resolve_expr(mixin);
+        }
+
+        // Traits' methods (and requirements) count as if they were declared in the class
+        let mut declaration = Resolver::declaration_of(
+            abstract_methods,
+            methods,
+            super_class
+                .as_ref()
+                .and_then(|super_class| match super_class {
+                    Expr::Variable { name, .. } => Some(name.lexeme.clone()),
+                    _ => None,
+                }),
+        );
+        for mixin in traits {
+            if let Expr::Variable { name, .. } = mixin {
+                if let Some(mixin) = self.declarations.get(&name.lexeme) {
+                    declaration.methods.extend(mixin.methods.iter().cloned());
+                    let required = mixin.abstract_methods.iter().cloned();
+                    declaration.abstract_methods.extend(required);
+                }
+            }
+        }
+        self.declarations.insert(name.lexeme.clone(), declaration);
+        if super_class.is_some() {
+            self.check_abstract_methods(name, abstract_methods);
+        }
+
+        // Holds the class being declared, it's used to check private member access
+        self.begin_scope();
+        self.peek_scopes()
+            .insert("#class".into(), State::new(true, true, name.clone()));
+
+        if super_class.is_some() {
+            self.current_class = ClassType::Subclass;
 
             self.begin_scope();
             self.peek_scopes()
@@ -224,11 +357,15 @@ impl Resolver<'_> {
             self.resolve_method(getter);
         }
 
+        for setter in setters {
+            self.resolve_method(setter);
+        }
+
         for static_method in static_methods {
             self.resolve_method(static_method);
         }
 
-        for method in methods {
+        for method in methods.iter().chain(private_methods) {
             self.resolve_method(method);
         }
 
@@ -238,6 +375,58 @@ impl Resolver<'_> {
             self.end_scope();
         }
 
+        self.end_scope();
+
+        self.current_class = enclosing_class;
+    }
+
+    fn visit_enum_stmt(&mut self, name: &Token, variants: &[Token]) {
+        self.declare(name);
+        self.define(name);
+
+        for (i, variant) in variants.iter().enumerate() {
+            if variants[..i].iter().any(|v| v.lexeme == variant.lexeme) {
+                RuntimeError {
+                    token: variant.clone(),
+                    message: format!(
+                        "Duplicate variant '{}' in enum '{}'.",
+                        variant.lexeme, name.lexeme
+                    ),
+                }
+                .error();
+            }
+        }
+    }
+
+    // Trait methods run as if they were declared inside the class they're mixed into,
+    // so they can use `this`, `super` and private members of that class.
+    fn visit_trait_stmt(&mut self, name: &Token, abstract_methods: &[Token], methods: &Vec<Stmt>) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Trait;
+
+        self.declare(name);
+        self.define(name);
+
+        let declaration = Resolver::declaration_of(abstract_methods, methods, None);
+        self.declarations.insert(name.lexeme.clone(), declaration);
+
+        self.begin_scope();
+        self.peek_scopes()
+            .insert("#class".into(), State::new(true, true, name.clone()));
+        self.peek_scopes()
+            .insert("super".into(), State::new(true, true, name.clone()));
+
+        self.begin_scope();
+        self.peek_scopes()
+            .insert("this".into(), State::new(true, true, name.clone()));
+
+        for method in methods {
+            self.resolve_method(method);
+        }
+
+        self.end_scope();
+        self.end_scope();
+
         self.current_class = enclosing_class;
     }
 
@@ -284,10 +473,36 @@ impl Resolver<'_> {
                 .error();
             }
 
+            if let FunctionType::Generator = self.current_function {
+                return RuntimeError {
+                    token: keyword.clone(),
+                    message: "Can't return a value from a generator.".into(),
+                }
+                .error();
+            }
+
             self.resolve_expr(value);
         }
     }
 
+    fn visit_yield_stmt(&mut self, keyword: &Token, value: &Expr) {
+        match self.current_function {
+            FunctionType::None => RuntimeError {
+                token: keyword.clone(),
+                message: "Can't yield from top-level code.".into(),
+            }
+            .error(),
+            FunctionType::Initializer => RuntimeError {
+                token: keyword.clone(),
+                message: "Can't yield from an initializer.".into(),
+            }
+            .error(),
+            _ => (),
+        }
+
+        self.resolve_expr(value);
+    }
+
     fn visit_var_stmt(&mut self, name: &Token, initializer: &Option<Expr>) {
         self.declare(name);
 
@@ -298,6 +513,107 @@ impl Resolver<'_> {
         self.define(name);
     }
 
+    // The loop variable lives in its own scope, a new one on every iteration
+    fn visit_for_in_stmt(&mut self, name: &Token, iterable: &Expr, body: &Stmt) {
+        self.resolve_expr(iterable);
+
+        self.begin_scope();
+        self.declare(name);
+        self.define(name);
+        self.resolve_stmt(body);
+        self.end_scope();
+    }
+
+    // Every case gets its own scope, holding the variables bound by its patterns
+    fn visit_match_stmt(&mut self, subject: &Expr, cases: &[MatchCase]) {
+        self.resolve_expr(subject);
+
+        let mut has_catch_all = false;
+        for case in cases {
+            if has_catch_all {
+                RuntimeError {
+                    token: case.keyword.clone(),
+                    message: "Unreachable case after a wildcard pattern.".into(),
+                }
+                .error();
+            }
+
+            let binds = case.patterns.iter().any(|p| !p.bindings().is_empty());
+            if case.patterns.len() > 1 && binds {
+                RuntimeError {
+                    token: case.keyword.clone(),
+                    message: "Can't bind variables in alternative patterns.".into(),
+                }
+                .error();
+            }
+
+            self.begin_scope();
+            for pattern in &case.patterns {
+                self.resolve_pattern(pattern);
+            }
+            if let Some(guard) = &case.guard {
+                self.resolve_expr(guard);
+            }
+            self.resolve_stmt(&case.body);
+            self.end_scope();
+
+            if case.guard.is_none() && case.patterns.iter().any(Pattern::is_irrefutable) {
+                has_catch_all = true;
+            }
+        }
+    }
+
+    fn resolve_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Wildcard => (),
+            Pattern::Binding(name) => {
+                self.declare(name);
+                self.define(name);
+            }
+            Pattern::Value(expr) => self.resolve_expr(expr),
+            Pattern::Instance { class, fields, .. } => {
+                self.resolve_expr(class);
+                for field in fields {
+                    self.resolve_pattern(field);
+                }
+            }
+        }
+    }
+
+    // Globals are checked by the environment, since they can be redeclared
+    fn visit_const_stmt(&mut self, name: &Token, initializer: &Expr) {
+        self.declare(name);
+        self.resolve_expr(initializer);
+        self.define(name);
+
+        if let Some(state) = self.scopes.last_mut().and_then(|s| s.get_mut(&name.lexeme)) {
+            state.is_const = true;
+        }
+    }
+
+    fn visit_destructure_stmt(&mut self, target: &Target, initializer: &Expr) {
+        let mut names: Vec<Token> = vec![];
+        for name in target.names() {
+            if names.iter().any(|other| other.lexeme == name.lexeme) {
+                RuntimeError {
+                    message: format!("Duplicate name '{}' in destructuring pattern.", name.lexeme),
+                    token: name,
+                }
+                .error();
+                continue;
+            }
+
+            self.declare(&name);
+            names.push(name);
+        }
+
+        self.resolve_expr(initializer);
+
+        for name in &names {
+            self.define(name);
+        }
+    }
+
     fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) {
         self.resolve_expr(condition);
         self.resolve_stmt(body);
@@ -307,6 +623,13 @@ impl Resolver<'_> {
         for i in (0..self.scopes.len()).rev() {
             if let Some(state) = self.scopes[i].get_mut(&name.lexeme) {
                 state.is_used = true;
+                if state.is_const {
+                    RuntimeError {
+                        token: name.clone(),
+                        message: format!("Cannot assign to constant '{}'.", name.lexeme),
+                    }
+                    .error();
+                }
                 break;
             }
         }
@@ -327,14 +650,33 @@ impl Resolver<'_> {
         }
     }
 
-    fn visit_get_expr(&mut self, expr: &Expr) {
-        self.resolve_expr(expr);
+    fn visit_get_expr(&mut self, name: &Token, object: &Expr) {
+        self.check_private_access(name, object);
+        self.resolve_expr(object);
+    }
+
+    fn visit_index_expr(&mut self, object: &Expr, index: &Expr) {
+        self.resolve_expr(object);
+        self.resolve_expr(index);
     }
 
     fn visit_grouping_expr(&mut self, expr: &Expr) {
         self.resolve_expr(expr);
     }
 
+    fn visit_list_expr(&mut self, elements: &[Expr]) {
+        for element in elements {
+            self.resolve_expr(element);
+        }
+    }
+
+    fn visit_map_expr(&mut self, entries: &[(Expr, Expr)]) {
+        for (key, value) in entries {
+            self.resolve_expr(key);
+            self.resolve_expr(value);
+        }
+    }
+
     fn visit_literal_expr(&self) {}
 
     fn visit_logical_expr(&mut self, left: &Expr, right: &Expr) {
@@ -342,9 +684,35 @@ impl Resolver<'_> {
         self.resolve_expr(right);
     }
 
-    fn visit_set_expr(&mut self, value: &Expr, object: &Expr) {
+    fn visit_set_expr(&mut self, name: &Token, value: &Expr, object: &Expr) {
+        self.check_private_access(name, object);
+        self.resolve_expr(value);
+        self.resolve_expr(object);
+    }
+
+    fn visit_set_index_expr(&mut self, object: &Expr, index: &Expr, value: &Expr) {
         self.resolve_expr(value);
         self.resolve_expr(object);
+        self.resolve_expr(index);
+    }
+
+    // Private members can only be reached through `this`, which also
+    // guarantees we're inside a class declaration.
+    fn check_private_access(&mut self, name: &Token, object: &Expr) {
+        if name.token_type != TokenType::PrivateIdentifier {
+            return;
+        }
+
+        if !matches!(object, Expr::This { .. }) {
+            RuntimeError {
+                token: name.clone(),
+                message: format!(
+                    "Private member '{}' can only be accessed through 'this'.",
+                    name.lexeme
+                ),
+            }
+            .error();
+        }
     }
 
     fn visit_super_expr(&mut self, expr: &Expr, keyword: &Token) {
@@ -363,7 +731,7 @@ impl Resolver<'_> {
                 }
                 .error();
             }
-            ClassType::Subclass => (),
+            ClassType::Subclass | ClassType::Trait => (),
         }
 
         self.resolve_local(expr, keyword);
@@ -409,20 +777,32 @@ impl stmt::Visitor<()> for Resolver<'_> {
     fn visit_stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Expression(expr) => self.visit_expression_stmt(expr),
-            Stmt::Class {
-                getters,
+            Stmt::Class(declaration) => self.visit_class_stmt(declaration),
+            Stmt::Enum { name, variants } => self.visit_enum_stmt(name, variants),
+            Stmt::Trait {
+                abstract_methods,
                 name,
                 methods,
-                static_methods,
-                super_class,
-            } => self.visit_class_stmt(getters, name, methods, static_methods, super_class),
+            } => self.visit_trait_stmt(name, abstract_methods, methods),
             Stmt::Var { name, initializer } => self.visit_var_stmt(name, initializer),
+            Stmt::Const { name, initializer } => self.visit_const_stmt(name, initializer),
+            Stmt::Destructure {
+                target,
+                initializer,
+            } => self.visit_destructure_stmt(target, initializer),
             Stmt::Block { statements } => self.visit_block_stmt(statements),
             Stmt::If {
                 condition,
                 then_branch,
                 else_branch,
             } => self.visit_if_stmt(condition, then_branch, else_branch),
+            Stmt::ForIn {
+                name,
+                iterable,
+                body,
+                ..
+            } => self.visit_for_in_stmt(name, iterable, body),
+            Stmt::Match { subject, cases, .. } => self.visit_match_stmt(subject, cases),
             Stmt::While { condition, body } => self.visit_while_stmt(condition, body),
             Stmt::Function {
                 name,
@@ -430,6 +810,7 @@ impl stmt::Visitor<()> for Resolver<'_> {
                 body,
             } => self.visit_function_stmt(name, parameters, body),
             Stmt::Return { keyword, value } => self.visit_return_stmt(keyword, value),
+            Stmt::Yield { keyword, value } => self.visit_yield_stmt(keyword, value),
         }
     }
 }
@@ -439,6 +820,8 @@ impl expr::Visitor<()> for Resolver<'_> {
         match expression {
             Expr::Binary { left, right, .. } => self.visit_binary_expr(left, right),
             Expr::Grouping { expression, .. } => self.visit_grouping_expr(expression),
+            Expr::List { elements, .. } => self.visit_list_expr(elements),
+            Expr::Map { entries, .. } => self.visit_map_expr(entries),
             Expr::Literal { .. } => self.visit_literal_expr(),
             Expr::Unary { right, .. } => self.visit_unary_expr(right),
             Expr::Variable { name, .. } => self.visit_var_expr(expression, name),
@@ -447,8 +830,20 @@ impl expr::Visitor<()> for Resolver<'_> {
             Expr::Call {
                 callee, arguments, ..
             } => self.visit_call_expr(callee, arguments),
-            Expr::Get { object, .. } => self.visit_get_expr(object),
-            Expr::Set { object, value, .. } => self.visit_set_expr(value, object),
+            Expr::Get { name, object, .. } => self.visit_get_expr(name, object),
+            Expr::Index { object, index, .. } => self.visit_index_expr(object, index),
+            Expr::Set {
+                name,
+                object,
+                value,
+                ..
+            } => self.visit_set_expr(name, value, object),
+            Expr::SetIndex {
+                object,
+                index,
+                value,
+                ..
+            } => self.visit_set_index_expr(object, index, value),
             Expr::This { name, .. } => self.visit_this_expr(expression, name),
             Expr::Super { keyword, .. } => self.visit_super_expr(expression, keyword),
         }
@@ -53,8 +53,22 @@ impl Scanner {
             ')' => self.add_token(TokenType::RightParen, Literal::None),
             '{' => self.add_token(TokenType::LeftBrace, Literal::None),
             '}' => self.add_token(TokenType::RightBrace, Literal::None),
+            '[' => self.add_token(TokenType::LeftBracket, Literal::None),
+            ']' => self.add_token(TokenType::RightBracket, Literal::None),
             ',' => self.add_token(TokenType::Comma, Literal::None),
-            '.' => self.add_token(TokenType::Dot, Literal::None),
+            ':' => self.add_token(TokenType::Colon, Literal::None),
+            '.' => {
+                let token_type = if !self.match_next_token('.') {
+                    TokenType::Dot
+                } else if self.match_next_token('.') {
+                    TokenType::DotDotDot
+                } else if self.match_next_token('=') {
+                    TokenType::DotDotEqual
+                } else {
+                    TokenType::DotDot
+                };
+                self.add_token(token_type, Literal::None)
+            }
             '-' => self.add_token(TokenType::Minus, Literal::None),
             '+' => self.add_token(TokenType::Plus, Literal::None),
             ';' => self.add_token(TokenType::Semicolon, Literal::None),
@@ -73,15 +87,14 @@ impl Scanner {
                 )
             }
             '=' => {
-                let is_matched = self.match_next_token('=');
-                self.add_token(
-                    if is_matched {
-                        TokenType::EqualEqual
-                    } else {
-                        TokenType::Equal
-                    },
-                    Literal::None,
-                )
+                let token_type = if self.match_next_token('=') {
+                    TokenType::EqualEqual
+                } else if self.match_next_token('>') {
+                    TokenType::FatArrow
+                } else {
+                    TokenType::Equal
+                };
+                self.add_token(token_type, Literal::None)
             }
             '<' => {
                 let is_matched = self.match_next_token('=');
@@ -122,6 +135,14 @@ impl Scanner {
             '\n' => self.line += 1,
             // String literals
             '"' => self.string(),
+            // Private class members: `#name`
+            '#' => {
+                if self.is_alpha(self.peek()) {
+                    self.private_identifier()
+                } else {
+                    error(self.line, "Expected a member name after '#'.");
+                }
+            }
 
             // If it isn't a string, then it only can be: number | variable | error
             _ => {
@@ -151,6 +172,14 @@ impl Scanner {
         }
     }
 
+    fn private_identifier(&mut self) {
+        while self.is_alpha_numeric(self.peek()) {
+            self.advance();
+        }
+
+        self.add_token(TokenType::PrivateIdentifier, Literal::None)
+    }
+
     fn number(&mut self) {
         while self.is_digit(self.peek()) {
             self.advance();
@@ -257,19 +286,26 @@ impl Scanner {
 
         hash.insert("and".into(), TokenType::And);
         hash.insert("class".into(), TokenType::Class);
+        hash.insert("const".into(), TokenType::Const);
         hash.insert("else".into(), TokenType::Else);
+        hash.insert("enum".into(), TokenType::Enum);
         hash.insert("false".into(), TokenType::False);
         hash.insert("for".into(), TokenType::For);
         hash.insert("fun".into(), TokenType::Fun);
         hash.insert("if".into(), TokenType::If);
+        hash.insert("in".into(), TokenType::In);
+        hash.insert("is".into(), TokenType::Is);
         hash.insert("nil".into(), TokenType::Nil);
         hash.insert("or".into(), TokenType::Or);
         hash.insert("return".into(), TokenType::Return);
         hash.insert("super".into(), TokenType::Super);
         hash.insert("this".into(), TokenType::This);
+        hash.insert("trait".into(), TokenType::Trait);
         hash.insert("true".into(), TokenType::True);
         hash.insert("var".into(), TokenType::Var);
         hash.insert("while".into(), TokenType::While);
+        hash.insert("with".into(), TokenType::With);
+        hash.insert("yield".into(), TokenType::Yield);
 
         hash
     }
@@ -281,7 +317,7 @@ mod tests {
 
     #[test]
     fn punctuators() {
-        let mut scanner = Scanner::new("(){};,+-*!===<=>=!=<>/.".into());
+        let mut scanner = Scanner::new("(){}[];,+-*!===<=>=!=<>/.=>:.. ..= ...".into());
         let tokens = scanner.scan_tokens();
 
         let expected = vec![
@@ -289,6 +325,8 @@ mod tests {
             Token::new(TokenType::RightParen, ")".into(), Literal::None, 1),
             Token::new(TokenType::LeftBrace, "{".into(), Literal::None, 1),
             Token::new(TokenType::RightBrace, "}".into(), Literal::None, 1),
+            Token::new(TokenType::LeftBracket, "[".into(), Literal::None, 1),
+            Token::new(TokenType::RightBracket, "]".into(), Literal::None, 1),
             Token::new(TokenType::Semicolon, ";".into(), Literal::None, 1),
             Token::new(TokenType::Comma, ",".into(), Literal::None, 1),
             Token::new(TokenType::Plus, "+".into(), Literal::None, 1),
@@ -303,6 +341,11 @@ mod tests {
             Token::new(TokenType::Greater, ">".into(), Literal::None, 1),
             Token::new(TokenType::Slash, "/".into(), Literal::None, 1),
             Token::new(TokenType::Dot, ".".into(), Literal::None, 1),
+            Token::new(TokenType::FatArrow, "=>".into(), Literal::None, 1),
+            Token::new(TokenType::Colon, ":".into(), Literal::None, 1),
+            Token::new(TokenType::DotDot, "..".into(), Literal::None, 1),
+            Token::new(TokenType::DotDotEqual, "..=".into(), Literal::None, 1),
+            Token::new(TokenType::DotDotDot, "...".into(), Literal::None, 1),
             Token::new(TokenType::Eof, "".into(), Literal::None, 1),
         ];
 
@@ -352,7 +395,8 @@ mod tests {
     #[test]
     fn keywords() {
         let mut scanner = Scanner::new(
-            "and class else false for if nil or print return super this true var while".into(),
+            "and class const else enum false for if in is nil or return super this trait true var while with yield"
+                .into(),
         );
 
         let tokens = scanner.scan_tokens();
@@ -360,19 +404,25 @@ mod tests {
         let expected_tokens = vec![
             Token::new(TokenType::And, "and".into(), Literal::None, 1),
             Token::new(TokenType::Class, "class".into(), Literal::None, 1),
+            Token::new(TokenType::Const, "const".into(), Literal::None, 1),
             Token::new(TokenType::Else, "else".into(), Literal::None, 1),
+            Token::new(TokenType::Enum, "enum".into(), Literal::None, 1),
             Token::new(TokenType::False, "false".into(), Literal::None, 1),
             Token::new(TokenType::For, "for".into(), Literal::None, 1),
             Token::new(TokenType::If, "if".into(), Literal::None, 1),
+            Token::new(TokenType::In, "in".into(), Literal::None, 1),
+            Token::new(TokenType::Is, "is".into(), Literal::None, 1),
             Token::new(TokenType::Nil, "nil".into(), Literal::None, 1),
             Token::new(TokenType::Or, "or".into(), Literal::None, 1),
-            Token::new(TokenType::Print, "print".into(), Literal::None, 1),
             Token::new(TokenType::Return, "return".into(), Literal::None, 1),
             Token::new(TokenType::Super, "super".into(), Literal::None, 1),
             Token::new(TokenType::This, "this".into(), Literal::None, 1),
+            Token::new(TokenType::Trait, "trait".into(), Literal::None, 1),
             Token::new(TokenType::True, "true".into(), Literal::None, 1),
             Token::new(TokenType::Var, "var".into(), Literal::None, 1),
             Token::new(TokenType::While, "while".into(), Literal::None, 1),
+            Token::new(TokenType::With, "with".into(), Literal::None, 1),
+            Token::new(TokenType::Yield, "yield".into(), Literal::None, 1),
             Token::new(TokenType::Eof, "".into(), Literal::None, 1),
         ];
 
@@ -382,6 +432,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn private_identifiers() {
+        let mut scanner = Scanner::new("this.#secret".into());
+        let tokens = scanner.scan_tokens();
+
+        let expected = vec![
+            Token::new(TokenType::This, "this".into(), Literal::None, 1),
+            Token::new(TokenType::Dot, ".".into(), Literal::None, 1),
+            Token::new(
+                TokenType::PrivateIdentifier,
+                "#secret".into(),
+                Literal::None,
+                1,
+            ),
+            Token::new(TokenType::Eof, "".into(), Literal::None, 1),
+        ];
+
+        assert_eq!(tokens.len(), expected.len());
+        for (index, token) in tokens.iter().enumerate() {
+            assert_eq!(*token, expected[index]);
+        }
+    }
+
     #[test]
     fn whistespaces() {
         let mut scanner = Scanner::new(
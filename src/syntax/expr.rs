@@ -32,16 +32,32 @@ pub enum Expr {
         uid: Id,
         expression: Box<Expr>,
     },
+    List {
+        uid: Id,
+        elements: Vec<Expr>,
+    },
     Literal {
         uid: Id,
         value: Literal,
     },
+    Map {
+        uid: Id,
+        brace: Token,
+        entries: Vec<(Expr, Expr)>,
+    },
     Set {
         uid: Id,
         name: Token,
         object: Box<Expr>,
         value: Box<Expr>,
     },
+    SetIndex {
+        uid: Id,
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
     Super {
         uid: Id,
         keyword: Token,
@@ -82,6 +98,12 @@ pub enum Expr {
         name: Token,
         object: Box<Expr>,
     },
+    Index {
+        uid: Id,
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
 }
 
 impl Expr {
@@ -89,14 +111,18 @@ impl Expr {
         match self {
             Expr::Binary { uid, .. } => *uid,
             Expr::Grouping { uid, .. } => *uid,
+            Expr::List { uid, .. } => *uid,
             Expr::Literal { uid, .. } => *uid,
+            Expr::Map { uid, .. } => *uid,
             Expr::Unary { uid, .. } => *uid,
             Expr::Variable { uid, .. } => *uid,
             Expr::Assign { uid, .. } => *uid,
             Expr::Logical { uid, .. } => *uid,
             Expr::Call { uid, .. } => *uid,
             Expr::Get { uid, .. } => *uid,
+            Expr::Index { uid, .. } => *uid,
             Expr::Set { uid, .. } => *uid,
+            Expr::SetIndex { uid, .. } => *uid,
             Expr::This { uid, .. } => *uid,
             Expr::Super { uid, .. } => *uid,
         }
@@ -1,5 +1,6 @@
 pub mod expr;
 pub mod token;
+pub mod pattern;
 pub mod value;
 pub mod token_type;
 pub mod stmt;
\ No newline at end of file
@@ -0,0 +1,74 @@
+use super::{expr::Expr, stmt::Stmt, token::Token};
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    // `_`, matches anything
+    Wildcard,
+    // A lone identifier matches anything and binds it to a new variable
+    Binding(Token),
+    // Literals and constants such as `Color.Red`, compared with `==`
+    Value(Expr),
+    // `Point(x, y)` matches instances of the class, the nested patterns are
+    // matched against the fields named after the parameters of its `init`.
+    // So `init(x, y)` must store them as `this.x` and `this.y`, a missing
+    // field is a runtime error.
+    Instance {
+        class: Expr,
+        paren: Token,
+        fields: Vec<Pattern>,
+    },
+}
+
+impl Pattern {
+    // Whether the pattern matches every value
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding(_))
+    }
+
+    pub fn bindings(&self) -> Vec<Token> {
+        match self {
+            Pattern::Binding(name) => vec![name.clone()],
+            Pattern::Instance { fields, .. } => fields.iter().flat_map(|f| f.bindings()).collect(),
+            Pattern::Wildcard | Pattern::Value(_) => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchCase {
+    pub keyword: Token,
+    pub patterns: Vec<Pattern>,
+    pub guard: Option<Expr>,
+    pub body: Box<Stmt>,
+}
+
+// What `var`, parameters and for-in loops bind values to
+#[derive(Debug, Clone)]
+pub enum Target {
+    Name(Token),
+    // `[a, b, ...rest]` binds list elements by position
+    List {
+        bracket: Token,
+        elements: Vec<Target>,
+        rest: Option<Token>,
+    },
+    // `{x, y}` binds instance fields or map keys by name
+    Object {
+        brace: Token,
+        names: Vec<Token>,
+    },
+}
+
+impl Target {
+    pub fn names(&self) -> Vec<Token> {
+        match self {
+            Target::Name(name) => vec![name.clone()],
+            Target::List { elements, rest, .. } => elements
+                .iter()
+                .flat_map(|element| element.names())
+                .chain(rest.iter().cloned())
+                .collect(),
+            Target::Object { names, .. } => names.clone(),
+        }
+    }
+}
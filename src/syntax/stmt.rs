@@ -1,4 +1,22 @@
-use super::{expr::Expr, token::Token};
+use super::{
+    expr::Expr,
+    pattern::{MatchCase, Target},
+    token::Token,
+};
+
+// Everything declared by `class Name < Super with Trait { ... }`
+#[derive(Debug, Clone)]
+pub struct ClassDeclaration {
+    pub abstract_methods: Vec<Token>,
+    pub getters: Vec<Stmt>,
+    pub name: Token,
+    pub methods: Vec<Stmt>,
+    pub private_methods: Vec<Stmt>,
+    pub setters: Vec<Stmt>,
+    pub static_methods: Vec<Stmt>,
+    pub super_class: Option<Expr>,
+    pub traits: Vec<Expr>,
+}
 
 pub trait Visitor<T> {
     fn visit_stmt(&mut self, stmt: &Stmt) -> T;
@@ -11,21 +29,44 @@ pub enum Stmt {
         name: Token,
         initializer: Option<Expr>,
     },
+    Const {
+        name: Token,
+        initializer: Expr,
+    },
+    // `var [a, b] = list;` and `var {x, y} = point;`
+    Destructure {
+        target: Target,
+        initializer: Expr,
+    },
     Block {
         statements: Vec<Stmt>,
     },
-    Class {
-        getters: Vec<Stmt>,
+    Class(ClassDeclaration),
+    Enum {
+        name: Token,
+        variants: Vec<Token>,
+    },
+    Trait {
+        abstract_methods: Vec<Token>,
         name: Token,
         methods: Vec<Stmt>,
-        static_methods: Vec<Stmt>,
-        super_class: Option<Expr>,
     },
     If {
         condition: Expr,
         then_branch: Box<Stmt>,
         else_branch: Option<Box<Stmt>>,
     },
+    ForIn {
+        name: Token,
+        keyword: Token,
+        iterable: Expr,
+        body: Box<Stmt>,
+    },
+    Match {
+        keyword: Token,
+        subject: Expr,
+        cases: Vec<MatchCase>,
+    },
     While {
         condition: Expr,
         body: Box<Stmt>,
@@ -39,4 +80,36 @@ pub enum Stmt {
         keyword: Token,
         value: Option<Expr>,
     },
+    Yield {
+        keyword: Token,
+        value: Expr,
+    },
+}
+
+impl Stmt {
+    // A function whose body yields is a generator. Yields inside nested
+    // functions and classes belong to them, so those aren't searched.
+    pub fn yields(statements: &[Stmt]) -> bool {
+        statements.iter().any(Stmt::contains_yield)
+    }
+
+    fn contains_yield(&self) -> bool {
+        match self {
+            Stmt::Yield { .. } => true,
+            Stmt::Block { statements } => Stmt::yields(statements),
+            Stmt::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                then_branch.contains_yield()
+                    || else_branch
+                        .as_ref()
+                        .is_some_and(|branch| branch.contains_yield())
+            }
+            Stmt::While { body, .. } | Stmt::ForIn { body, .. } => body.contains_yield(),
+            Stmt::Match { cases, .. } => cases.iter().any(|case| case.body.contains_yield()),
+            _ => false,
+        }
+    }
 }
@@ -5,6 +5,9 @@ pub enum TokenType {
     RightParen,
     LeftBrace, 
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     Minus,
@@ -22,29 +25,41 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    FatArrow,
+    DotDot,
+    DotDotDot,
+    DotDotEqual,
 
     // Literals
     Identifier,
+    PrivateIdentifier,
     String,
     Number,
 
     // Keywords
     And, 
     Class,
+    Const,
     Else,
+    Enum,
     False,
     Fun,
     For,
     If,
+    In,
+    Is,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Trait,
     True,
     Var,
     While,
+    With,
+    Yield,
 
     Eof,
 }
\ No newline at end of file
@@ -1,17 +1,69 @@
+use std::{cell::RefCell, rc::Rc};
+
 use crate::impls::{
-    class::{Class, ClassInstanceRef},
+    class::{ClassInstanceRef, ClassRef},
+    date::Date,
+    enums::{EnumRef, EnumVariantRef},
     function::{Function, NativeFunction},
+    generator::GeneratorRef,
+    map::MapRef,
+    namespace::NamespaceRef,
+    traits::TraitRef,
 };
 
+// Lists are shared and mutable, like class instances
+pub type ListRef = Rc<RefCell<Vec<Value>>>;
+
+// `start..end` or `start..=end`, evaluated lazily by `for-in` loops
+#[derive(Debug, Clone, Copy)]
+pub struct Range {
+    pub start: f64,
+    pub end: f64,
+    pub inclusive: bool,
+}
+
 /// Represents all possibles values in the language
 #[derive(Debug, Clone)]
 pub enum Value {
     Boolean(bool),
-    Class(Class),
+    Class(ClassRef),
     ClassInstance(ClassInstanceRef),
+    Date(Date),
+    Enum(EnumRef),
+    EnumVariant(EnumVariantRef),
+    List(ListRef),
+    Map(MapRef),
+    Namespace(NamespaceRef),
     Number(f64),
+    Range(Range),
     String(String),
     Function(Function),
+    Generator(GeneratorRef),
     NativeFunction(NativeFunction),
+    Trait(TraitRef),
     Nil,
 }
+
+impl Value {
+    /// Name of the value's type, as returned by the `type()` native
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Boolean(_) => "boolean",
+            Value::Class(_) => "class",
+            Value::ClassInstance(_) => "instance",
+            Value::Date(_) => "date",
+            Value::Enum(_) => "enum",
+            Value::EnumVariant(_) => "variant",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Namespace(_) => "namespace",
+            Value::Number(_) => "number",
+            Value::Range(_) => "range",
+            Value::String(_) => "string",
+            Value::Function(_) | Value::NativeFunction(_) => "function",
+            Value::Generator(_) => "generator",
+            Value::Trait(_) => "trait",
+            Value::Nil => "nil",
+        }
+    }
+}
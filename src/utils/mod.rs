@@ -1 +1,2 @@
-pub mod id_factory;
\ No newline at end of file
+pub mod id_factory;
+pub mod random;